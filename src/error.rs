@@ -1,15 +1,31 @@
 //! Common errors using the [`error-chain`](https://docs.rs/error-chain) library
 
+// `error-chain` still implements the deprecated `Error::description` and
+// `Error::cause` methods in the code it generates.
+#![allow(deprecated)]
+
 error_chain!{
     foreign_links {
         Io(::std::io::Error);
     }
     errors {
+        AmbiguousPath(path: ::std::path::PathBuf, names: Vec<String>) {
+            description("ambiguous path")
+            display("ambiguous path '{}' matches: {}", path.to_string_lossy(), names.join(", "))
+        }
         // DirectoryExpected(path: ::std::path::PathBuf) {
         DirectoryExpected {
             description("directory expected")
             display("directory expected")
         }
+        DuplicateVolume(sequence_number: u16) {
+            description("duplicate volume in volume set")
+            display("duplicate volume in volume set: {}", sequence_number)
+        }
+        EmptyVolumeSet {
+            description("empty volume set")
+            display("empty volume set")
+        }
         FileExpected {
             description("file expected")
             display("file expected")
@@ -18,6 +34,26 @@ error_chain!{
             description("no primary volume descriptor found")
             display("no primary volume descriptor found")
         }
+        InvalidChecksum {
+            description("invalid checksum")
+            display("invalid checksum")
+        }
+        NoBootCatalog {
+            description("no boot catalog found")
+            display("no boot catalog found")
+        }
+        NoEfiBootImage {
+            description("no efi boot image found")
+            display("no efi boot image found")
+        }
+        NoEnhancedVolumeDescriptor {
+            description("no enhanced volume descriptor found")
+            display("no enhanced volume descriptor found")
+        }
+        NoJolietVolumeDescriptor {
+            description("no joliet volume descriptor found")
+            display("no joliet volume descriptor found")
+        }
         NotFound(path: ::std::path::PathBuf) {
             description("path not found")
             display("path not found: '{}'", path.to_string_lossy())
@@ -34,10 +70,26 @@ error_chain!{
             description("not enough data")
             display("not enough data")
         }
+        PathTableMismatch {
+            description("path tables do not match")
+            display("path tables do not match")
+        }
+        SessionNotFound(index: usize) {
+            description("session not found")
+            display("session not found: {}", index)
+        }
         UnknownDescriptorType(t: u8) {
             description("unknown descriptor type")
             display("unknown descriptor type: {}", t)
         }
+        VolumeNotFound(sequence_number: u16) {
+            description("volume not found")
+            display("volume not found: {}", sequence_number)
+        }
+        VolumeSetMismatch(expected: String, found: String) {
+            description("volume set mismatch")
+            display("volume set mismatch: expected '{}', found '{}'", expected, found)
+        }
     }
 }
 
@@ -0,0 +1,151 @@
+/// The FAT variant of a filesystem, given by its number of clusters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FatType {
+    Fat12,
+    Fat16,
+    Fat32,
+}
+
+/// The BIOS Parameter Block of a FAT filesystem boot sector.
+#[derive(Debug)]
+pub struct BootSector {
+    pub bytes_per_sector: u16,
+    pub sectors_per_cluster: u8,
+    pub reserved_sectors: u16,
+    pub fat_count: u8,
+    pub root_entries: u16,
+    pub total_sectors: u32,
+    pub fat_size: u32,
+    pub root_cluster: u32,
+}
+
+impl BootSector {
+    pub fn parse(bytes: &[u8]) -> ::error::Result<Self> {
+        match parser::boot_sector(bytes) {
+            Ok((_, bs)) => Ok(bs),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Get the size of a cluster, in bytes.
+    pub fn cluster_size(&self) -> u64 {
+        self.bytes_per_sector as u64 * self.sectors_per_cluster as u64
+    }
+
+    /// Get the offset of the first File Allocation Table.
+    pub fn fat_offset(&self) -> u64 {
+        self.reserved_sectors as u64 * self.bytes_per_sector as u64
+    }
+
+    /// Get the offset of the fixed root directory (FAT12 and FAT16 only).
+    pub fn root_offset(&self) -> u64 {
+        self.fat_offset() + self.fat_count as u64 * self.fat_size as u64 * self.bytes_per_sector as u64
+    }
+
+    /// Get the size of the fixed root directory (FAT12 and FAT16 only).
+    pub fn root_size(&self) -> u64 {
+        self.root_entries as u64 * 32
+    }
+
+    /// Get the offset of the data region, where cluster 2 starts.
+    pub fn data_offset(&self) -> u64 {
+        let bps = self.bytes_per_sector as u64;
+        self.root_offset() + self.root_size().div_ceil(bps) * bps
+    }
+
+    /// Get the size of the whole filesystem, in bytes.
+    pub fn size(&self) -> u64 {
+        self.total_sectors as u64 * self.bytes_per_sector as u64
+    }
+
+    /// Get the number of data clusters of the filesystem.
+    pub fn cluster_count(&self) -> u64 {
+        self.size().saturating_sub(self.data_offset()) / self.cluster_size()
+    }
+
+    /// Get the FAT variant of the filesystem.
+    pub fn fat_type(&self) -> FatType {
+        match self.cluster_count() {
+            0..=4084 => FatType::Fat12,
+            4085..=65524 => FatType::Fat16,
+            _ => FatType::Fat32,
+        }
+    }
+}
+
+mod parser {
+
+    use nom::be_u8;
+    use nom::le_u16;
+    use nom::le_u32;
+
+    use super::BootSector;
+
+    #[rustfmt::skip]
+    named!(pub boot_sector(&[u8]) -> BootSector,
+        do_parse!(
+                                take!(11)                                  >>
+            bytes_per_sector:   verify!(le_u16, |x: u16| x.is_power_of_two() && x >= 512) >>
+            sectors_per_cluster: verify!(be_u8, |x: u8| x.is_power_of_two()) >>
+            reserved_sectors:   le_u16                                     >>
+            fat_count:          verify!(be_u8, |x: u8| x > 0)              >>
+            root_entries:       le_u16                                     >>
+            total_sectors_16:   le_u16                                     >>
+                                take!(1)                                   >>
+            fat_size_16:        le_u16                                     >>
+                                take!(8)                                   >>
+            total_sectors_32:   le_u32                                     >>
+            fat_size_32:        le_u32                                     >>
+                                take!(4)                                   >>
+            root_cluster:       le_u32                                     >>
+                                take!(462)                                 >>
+                                tag!(b"\x55\xAA")                          >>
+                                (BootSector {
+                                    bytes_per_sector,
+                                    sectors_per_cluster,
+                                    reserved_sectors,
+                                    fat_count,
+                                    root_entries,
+                                    total_sectors: if total_sectors_16 != 0 {
+                                        total_sectors_16 as u32
+                                    } else {
+                                        total_sectors_32
+                                    },
+                                    fat_size: if fat_size_16 != 0 {
+                                        fat_size_16 as u32
+                                    } else {
+                                        fat_size_32
+                                    },
+                                    root_cluster,
+                                })
+        )
+    );
+
+    #[cfg(test)]
+    mod tests {
+
+        use super::super::BootSector;
+        use super::super::FatType;
+
+        #[test]
+        fn test_boot_sector() {
+            let mut buf = vec![0; 512];
+            buf[..3].copy_from_slice(b"\xEB\x3C\x90");
+            buf[11..24].copy_from_slice(b"\x00\x02\x04\x01\x00\x02\x00\x02\x40\x0b\xf0\x09\x00");
+            buf[510..].copy_from_slice(b"\x55\xAA");
+            let bs = BootSector::parse(&buf).unwrap();
+            assert_eq!(bs.bytes_per_sector, 512);
+            assert_eq!(bs.cluster_size(), 2048);
+            assert_eq!(bs.total_sectors, 2880);
+            assert_eq!(bs.fat_offset(), 512);
+            assert_eq!(bs.root_offset(), 512 + 2 * 9 * 512);
+            assert_eq!(bs.data_offset(), bs.root_offset() + 512 * 32);
+            assert_eq!(bs.fat_type(), FatType::Fat12);
+        }
+
+        #[test]
+        fn test_invalid_boot_sector() {
+            assert!(BootSector::parse(&[0; 512]).is_err());
+        }
+    }
+}
@@ -0,0 +1,198 @@
+/// The attribute flag of read-only entries.
+pub const ATTR_READ_ONLY: u8 = 0x01;
+/// The attribute flag of hidden entries.
+pub const ATTR_HIDDEN: u8 = 0x02;
+/// The attribute flag of volume labels.
+pub const ATTR_VOLUME_ID: u8 = 0x08;
+/// The attribute flag of directories.
+pub const ATTR_DIRECTORY: u8 = 0x10;
+/// The attributes marking a long file name entry.
+pub const ATTR_LONG_NAME: u8 = 0x0F;
+
+/// A directory entry of a FAT filesystem.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DirEntry {
+    pub name: String,
+    pub attributes: u8,
+    pub cluster: u32,
+    pub size: u32,
+}
+
+impl DirEntry {
+    /// Create the entry of the root directory, starting at `cluster`.
+    pub fn root(cluster: u32) -> Self {
+        Self {
+            name: String::new(),
+            attributes: ATTR_DIRECTORY,
+            cluster,
+            size: 0,
+        }
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.attributes & ATTR_DIRECTORY != 0
+    }
+
+    /// Parse all the entries of a directory, skipping the `.` and `..`
+    /// entries as well as deleted entries and volume labels.
+    pub fn parse_all(bytes: &[u8]) -> Vec<Self> {
+        let mut entries = Vec::new();
+        let mut long_name: Vec<(u8, u8, &[u8])> = Vec::new();
+
+        for raw in bytes.chunks(32).filter(|raw| raw.len() == 32) {
+            match (raw[0], raw[11]) {
+                (0x00, _) => break,
+                (0xE5, _) => long_name.clear(),
+                (_, ATTR_LONG_NAME) => long_name.push((raw[0] & 0x1F, raw[13], raw)),
+                (_, attr) if attr & ATTR_VOLUME_ID != 0 => long_name.clear(),
+                (b'.', _) if raw[1..11].iter().all(|&b| b == b' ' || b == b'.') => long_name.clear(),
+                _ => {
+                    let name = parser::long_name(&long_name, &raw[..11])
+                        .unwrap_or_else(|| parser::short_name(&raw[..11], raw[12]));
+                    if let Ok((_, entry)) = parser::entry(raw, name) {
+                        entries.push(entry);
+                    }
+                    long_name.clear();
+                }
+            }
+        }
+
+        entries
+    }
+}
+
+mod parser {
+
+    use nom::be_u8;
+    use nom::le_u16;
+    use nom::le_u32;
+
+    use super::DirEntry;
+
+    /// The flag of the NT reserved byte marking a lowercase base name.
+    const LOWERCASE_BASE: u8 = 0x08;
+    /// The flag of the NT reserved byte marking a lowercase extension.
+    const LOWERCASE_EXT: u8 = 0x10;
+
+    /// Compute the checksum of a short name, as recorded in the long file
+    /// name entries that precede it.
+    pub fn checksum(short: &[u8]) -> u8 {
+        short
+            .iter()
+            .fold(0u8, |sum, &b| (sum >> 1 | (sum & 1) << 7).wrapping_add(b))
+    }
+
+    /// Decode a short (8.3) name.
+    pub fn short_name(short: &[u8], flags: u8) -> String {
+        let decode = |bytes: &[u8], lower: bool| {
+            let text = String::from_utf8_lossy(bytes).trim_end().to_owned();
+            if lower { text.to_lowercase() } else { text }
+        };
+
+        let mut base = short[..8].to_vec();
+        if base[0] == 0x05 {
+            base[0] = 0xE5;
+        }
+
+        let base = decode(&base, flags & LOWERCASE_BASE != 0);
+        let ext = decode(&short[8..11], flags & LOWERCASE_EXT != 0);
+        if ext.is_empty() {
+            base
+        } else {
+            format!("{}.{}", base, ext)
+        }
+    }
+
+    /// Decode a long file name from its entries, given in on-disk order.
+    ///
+    /// Returns `None` if there are no entries or if their checksum does not
+    /// match the short name.
+    pub fn long_name(parts: &[(u8, u8, &[u8])], short: &[u8]) -> Option<String> {
+        let sum = checksum(short);
+        if parts.is_empty() || parts.iter().any(|&(_, s, _)| s != sum) {
+            return None;
+        }
+
+        let mut sorted = parts.to_vec();
+        sorted.sort_by_key(|&(seq, _, _)| seq);
+
+        let units = sorted
+            .iter()
+            .flat_map(|&(_, _, raw)| {
+                raw[1..11]
+                    .chunks(2)
+                    .chain(raw[14..26].chunks(2))
+                    .chain(raw[28..32].chunks(2))
+            })
+            .map(|c| c[0] as u16 | (c[1] as u16) << 8)
+            .take_while(|&unit| unit != 0x0000)
+            .collect::<Vec<u16>>();
+
+        Some(String::from_utf16_lossy(&units))
+    }
+
+    #[rustfmt::skip]
+    named_args!(pub entry(name: String)<DirEntry>,
+        do_parse!(
+                        take!(11)                            >>
+            attributes: be_u8                                >>
+                        take!(8)                             >>
+            high:       le_u16                               >>
+                        take!(4)                             >>
+            low:        le_u16                               >>
+            size:       le_u32                               >>
+                        (DirEntry {
+                            name,
+                            attributes,
+                            cluster: (high as u32) << 16 | low as u32,
+                            size,
+                        })
+        )
+    );
+
+    #[cfg(test)]
+    mod tests {
+
+        use super::super::DirEntry;
+
+        #[test]
+        fn test_short_name() {
+            assert_eq!(super::short_name(b"BOOTX64 EFI", 0), "BOOTX64.EFI");
+            assert_eq!(super::short_name(b"EFI        ", 0), "EFI");
+            assert_eq!(super::short_name(b"GRUB    CFG", 0x18), "grub.cfg");
+        }
+
+        #[test]
+        fn test_parse_all() {
+            let mut bytes = vec![0; 32 * 5];
+            // a `.` entry, which is skipped
+            bytes[..11].copy_from_slice(b".          ");
+            bytes[11] = 0x10;
+            // a long file name for `grubx64.efi`
+            let sum = super::checksum(b"GRUBX64 EFI");
+            bytes[32] = 0x41;
+            bytes[43] = 0x0F;
+            bytes[45] = sum;
+            let name = "grubx64.efi".encode_utf16().collect::<Vec<u16>>();
+            let slots = (1..11).step_by(2).chain((14..26).step_by(2)).chain((28..32).step_by(2));
+            for (i, slot) in slots.enumerate() {
+                let unit = name.get(i).cloned().unwrap_or(if i == name.len() { 0 } else { 0xFFFF });
+                bytes[32 + slot] = unit as u8;
+                bytes[32 + slot + 1] = (unit >> 8) as u8;
+            }
+            // the short entry of `grubx64.efi`
+            bytes[64..75].copy_from_slice(b"GRUBX64 EFI");
+            bytes[64 + 26] = 5;
+            bytes[64 + 28] = 0x10;
+            // a deleted entry
+            bytes[96..107].copy_from_slice(b"\xE5ELETED TXT");
+
+            let entries = DirEntry::parse_all(&bytes);
+            assert_eq!(entries.len(), 1);
+            assert_eq!(entries[0].name, "grubx64.efi");
+            assert_eq!(entries[0].cluster, 5);
+            assert_eq!(entries[0].size, 16);
+            assert!(!entries[0].is_dir());
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::cmp::min;
+use std::io::Read;
+use std::io::Result;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+use utils::io::seek_position;
+
+/// Readable file located on a FAT filesystem.
+pub struct FatFile<'a, H: 'a>
+where
+    H: Seek + Read,
+{
+    handle: &'a mut H,
+    clusters: Vec<u64>,
+    cluster_size: u64,
+    length: u64,
+    pos: u64,
+}
+
+impl<'a, H: 'a> FatFile<'a, H>
+where
+    H: Seek + Read,
+{
+    /// Open a file of `length` bytes stored in clusters starting at the
+    /// given offsets of `handle`.
+    pub(in fat) fn new(handle: &'a mut H, clusters: Vec<u64>, cluster_size: u64, length: u64) -> Self {
+        // never read past the end of the cluster chain
+        let length = min(length, clusters.len() as u64 * cluster_size);
+        Self {
+            handle,
+            clusters,
+            cluster_size,
+            length,
+            pos: 0,
+        }
+    }
+}
+
+impl<'a, H: 'a> Read for FatFile<'a, H>
+where
+    H: Seek + Read,
+{
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        if self.pos >= self.length {
+            return Ok(0);
+        }
+
+        // only read up to the end of the current cluster
+        let index = (self.pos / self.cluster_size) as usize;
+        let offset = self.pos % self.cluster_size;
+        let remaining = min(self.cluster_size - offset, self.length - self.pos);
+        let size = min(remaining, buffer.len() as u64) as usize;
+
+        self.handle.seek(SeekFrom::Start(self.clusters[index] + offset))?;
+        let bytes_read = self.handle.read(&mut buffer[..size])?;
+        self.pos += bytes_read as u64;
+        Ok(bytes_read)
+    }
+}
+
+impl<'a, H: 'a> Seek for FatFile<'a, H>
+where
+    H: Seek + Read,
+{
+    fn seek(&mut self, whence: SeekFrom) -> Result<u64> {
+        self.pos = seek_position(self.pos, self.length, whence)?;
+        Ok(self.pos)
+    }
+}
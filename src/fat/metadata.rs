@@ -0,0 +1,61 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use super::entry::DirEntry;
+use super::entry::ATTR_HIDDEN;
+use super::entry::ATTR_READ_ONLY;
+
+/// Metadata information about a FAT filesystem resource.
+///
+/// Similar to [`std::fs::Metadata`].
+///
+/// [`std::fs::Metadata`]: https://doc.rust-lang.org/std/fs/struct.Metadata.html
+#[derive(Clone, Debug)]
+pub struct Metadata {
+    path: PathBuf,
+    entry: DirEntry,
+}
+
+impl Metadata {
+    pub(in fat) fn new(path: PathBuf, entry: DirEntry) -> Self {
+        Self { path, entry }
+    }
+
+    /// Returns whether this metadata is for a directory.
+    pub fn is_dir(&self) -> bool {
+        self.entry.is_dir()
+    }
+
+    /// Returns whether this metadata is for a regular file.
+    pub fn is_file(&self) -> bool {
+        !self.is_dir()
+    }
+
+    /// Returns whether this resource is marked as hidden.
+    pub fn is_hidden(&self) -> bool {
+        self.entry.attributes & ATTR_HIDDEN != 0
+    }
+
+    /// Returns whether this resource is marked as read-only.
+    pub fn is_read_only(&self) -> bool {
+        self.entry.attributes & ATTR_READ_ONLY != 0
+    }
+
+    /// Return the size of the file, in bytes, this metadata is for.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u64 {
+        self.entry.size as u64
+    }
+
+    /// Return the name of the resource this metadata is for.
+    ///
+    /// The long file name is used when there is one.
+    pub fn name(&self) -> &str {
+        &self.entry.name
+    }
+
+    /// Return the absolute path to the resource this metadata is for.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
@@ -0,0 +1,364 @@
+//! [`FAT`] filesystem reader, for El Torito boot images.
+//!
+//! UEFI-bootable optical medias embed an EFI System Partition as a FAT image
+//! referenced from the El Torito boot catalog. The `fat` module supports
+//! reading FAT12, FAT16 and FAT32 filesystems with the same interface as
+//! [`IsoFs`], so that the embedded image can be explored in place.
+//!
+//! # Examples
+//!
+//! Open the EFI System Partition of an ISO image and read its boot loader:
+//!
+//! ```rust,no_run
+//! use std::io::Read;
+//!
+//! let mut iso = opticaldisc::iso::IsoFs::from_path("image.iso").unwrap();
+//! let mut fat = iso.open_efi_partition().unwrap();
+//! let mut loader = Vec::new();
+//! fat.open_file("EFI/BOOT/BOOTX64.EFI").unwrap().read_to_end(&mut loader);
+//! ```
+//!
+//! [`FAT`]: https://en.wikipedia.org/wiki/File_Allocation_Table
+//! [`IsoFs`]: ../iso/struct.IsoFs.html
+
+mod boot;
+mod entry;
+mod file;
+mod metadata;
+
+pub use self::boot::FatType;
+pub use self::file::FatFile;
+pub use self::metadata::Metadata;
+
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::Path;
+use std::path::PathBuf;
+
+use super::error::ErrorKind;
+use super::error::Result;
+
+pub(crate) use self::boot::BootSector;
+
+use self::entry::DirEntry;
+
+/// A FAT filesystem.
+pub struct FatFs<H: Read + Seek> {
+    handle: H,
+    boot: BootSector,
+    fat: Vec<u8>,
+}
+
+// Constructor
+impl<H: Read + Seek> FatFs<H> {
+    /// Open a `FatFs` stored in the given handle.
+    ///
+    /// The boot sector is read at the start of the handle, and the first
+    /// File Allocation Table is loaded in memory.
+    pub fn new(mut handle: H) -> Result<Self> {
+        let mut buff = [0; 512];
+        handle.seek(SeekFrom::Start(0))?;
+        handle.read_exact(&mut buff)?;
+        let boot = BootSector::parse(&buff)?;
+
+        // check the table fits in the image before allocating it
+        let fat_length = boot.fat_size as u64 * boot.bytes_per_sector as u64;
+        if boot.fat_offset() + fat_length > handle.seek(SeekFrom::End(0))? {
+            bail!(::std::io::Error::new(
+                ::std::io::ErrorKind::UnexpectedEof,
+                "file allocation table exceeds the image",
+            ));
+        }
+
+        let mut fat = vec![0; fat_length as usize];
+        handle.seek(SeekFrom::Start(boot.fat_offset()))?;
+        handle.read_exact(&mut fat)?;
+
+        Ok(Self { handle, boot, fat })
+    }
+}
+
+// Constructor from byte buffer
+impl<B: AsRef<[u8]>> FatFs<::std::io::Cursor<B>> {
+    /// Open a `FatFs` contained in a buffer of bytes.
+    pub fn from_buffer(buffer: B) -> Result<Self> {
+        Self::new(::std::io::Cursor::new(buffer))
+    }
+}
+
+// Common methods
+impl<H: Read + Seek> FatFs<H> {
+    /// Get the FAT variant of the filesystem.
+    pub fn fat_type(&self) -> FatType {
+        self.boot.fat_type()
+    }
+
+    /// Get the cluster following `cluster` in its chain, if any.
+    fn next_cluster(&self, cluster: u32) -> Option<u32> {
+        let n = cluster as usize;
+        let word = |i: usize| self.fat.get(i..i + 2).map(|b| b[0] as u32 | (b[1] as u32) << 8);
+        let next = match self.boot.fat_type() {
+            FatType::Fat12 => word(n + n / 2).map(|x| if n.is_multiple_of(2) { x & 0xFFF } else { x >> 4 })?,
+            FatType::Fat16 => word(n * 2)?,
+            FatType::Fat32 => (word(n * 4)? | word(n * 4 + 2)? << 16) & 0x0FFF_FFFF,
+        };
+        let end = match self.boot.fat_type() {
+            FatType::Fat12 => 0xFF7,
+            FatType::Fat16 => 0xFFF7,
+            FatType::Fat32 => 0x0FFF_FFF7,
+        };
+        if next >= 2 && next < end {
+            Some(next)
+        } else {
+            None
+        }
+    }
+
+    /// Get the list of clusters of the chain starting at `cluster`.
+    fn chain(&self, cluster: u32) -> Vec<u32> {
+        let mut clusters = Vec::new();
+        let mut current = if cluster >= 2 { Some(cluster) } else { None };
+        // a corrupted table may contain loops, which a chain can never
+        // be longer than the number of clusters to detect
+        let max_length = self.boot.cluster_count() as usize;
+        while let Some(cluster) = current {
+            if clusters.len() > max_length {
+                break;
+            }
+            clusters.push(cluster);
+            current = self.next_cluster(cluster);
+        }
+        clusters
+    }
+
+    /// Get the offset of the given data cluster.
+    fn cluster_offset(&self, cluster: u32) -> u64 {
+        self.boot.data_offset() + (cluster as u64 - 2) * self.boot.cluster_size()
+    }
+
+    /// Read the entries of the given directory.
+    fn children(&mut self, dir: &DirEntry) -> Result<Vec<DirEntry>> {
+        let mut bytes = Vec::new();
+
+        if dir.cluster == 0 && self.boot.fat_type() != FatType::Fat32 {
+            // the root directory of FAT12 and FAT16 has a fixed location
+            bytes.resize(self.boot.root_size() as usize, 0);
+            self.handle.seek(SeekFrom::Start(self.boot.root_offset()))?;
+            self.handle.read_exact(&mut bytes)?;
+        } else {
+            let size = self.boot.cluster_size() as usize;
+            for cluster in self.chain(dir.cluster) {
+                let start = bytes.len();
+                bytes.resize(start + size, 0);
+                self.handle.seek(SeekFrom::Start(self.cluster_offset(cluster)))?;
+                self.handle.read_exact(&mut bytes[start..])?;
+            }
+        }
+
+        Ok(DirEntry::parse_all(&bytes))
+    }
+
+    /// Get the entry of the resource at the given path.
+    ///
+    /// Names are compared case-insensitively, as FAT filesystems do.
+    fn entry(&mut self, path: &Path) -> Result<(PathBuf, DirEntry)> {
+        let root = match self.boot.fat_type() {
+            FatType::Fat32 => DirEntry::root(self.boot.root_cluster),
+            _ => DirEntry::root(0),
+        };
+
+        let mut stack = vec![(PathBuf::from("/"), root.clone())];
+        for component in path.components() {
+            use std::path::Component::*;
+            match component {
+                Prefix(_) | RootDir => stack.truncate(1),
+                CurDir => (),
+                ParentDir => {
+                    if stack.len() > 1 {
+                        stack.pop();
+                    }
+                }
+                Normal(name) => {
+                    let (parent_path, parent) = stack.last().cloned().unwrap();
+                    if !parent.is_dir() {
+                        bail!(ErrorKind::DirectoryExpected);
+                    }
+                    let name = name.to_string_lossy();
+                    let child = self
+                        .children(&parent)?
+                        .into_iter()
+                        .find(|e| e.name.to_lowercase() == name.to_lowercase());
+                    match child {
+                        Some(entry) => stack.push((parent_path.join(&entry.name), entry)),
+                        None => bail!(ErrorKind::NotFound(path.to_path_buf())),
+                    }
+                }
+            }
+        }
+
+        Ok(stack.pop().unwrap())
+    }
+
+    /// Get the contents of a directory.
+    ///
+    /// # Errors
+    ///
+    /// * [`NotFound`](../error/enum.ErrorKind.html#variant.NotFound)
+    ///   when the resource could not be found
+    /// * [`DirectoryExpected`](../error/enum.ErrorKind.html#variant.DirectoryExpected)
+    ///   when the resource is not a directory
+    pub fn read_dir<P: AsRef<Path>>(&mut self, path: P) -> Result<Vec<Metadata>> {
+        let (path, entry) = self.entry(path.as_ref())?;
+        if !entry.is_dir() {
+            bail!(ErrorKind::DirectoryExpected);
+        }
+        let contents = self
+            .children(&entry)?
+            .into_iter()
+            .map(|child| Metadata::new(path.join(&child.name), child))
+            .collect();
+        Ok(contents)
+    }
+
+    /// Get metadata about a resource located at the given path.
+    ///
+    /// # Errors
+    ///
+    /// * [`NotFound`](../error/enum.ErrorKind.html#variant.NotFound)
+    ///   when the resource could not be found
+    pub fn metadata<P: AsRef<Path>>(&mut self, path: P) -> Result<Metadata> {
+        self.entry(path.as_ref())
+            .map(|(path, entry)| Metadata::new(path, entry))
+    }
+
+    /// Check if the given path maps to a directory on the filesystem.
+    pub fn is_dir<P: AsRef<Path>>(&mut self, path: P) -> bool {
+        self.entry(path.as_ref())
+            .map(|(_, e)| e.is_dir())
+            .unwrap_or(false)
+    }
+
+    /// Check if the given path maps to a file on the filesystem.
+    pub fn is_file<P: AsRef<Path>>(&mut self, path: P) -> bool {
+        self.entry(path.as_ref())
+            .map(|(_, e)| !e.is_dir())
+            .unwrap_or(false)
+    }
+
+    /// Check if a resource with the given path exists on the filesystem.
+    pub fn exists<P: AsRef<Path>>(&mut self, path: P) -> bool {
+        self.entry(path.as_ref()).is_ok()
+    }
+
+    /// Open the file located at the given path.
+    ///
+    /// # Errors
+    ///
+    /// * [`NotFound`](../error/enum.ErrorKind.html#variant.NotFound)
+    ///   when the resource could not be found
+    /// * [`FileExpected`](../error/enum.ErrorKind.html#variant.FileExpected)
+    ///   when the resource is a directory
+    pub fn open_file<'a, P: AsRef<Path>>(&'a mut self, path: P) -> Result<FatFile<'a, H>> {
+        let (_, entry) = self.entry(path.as_ref())?;
+        if entry.is_dir() {
+            bail!(ErrorKind::FileExpected);
+        }
+        let extents = self
+            .chain(entry.cluster)
+            .into_iter()
+            .map(|cluster| self.cluster_offset(cluster))
+            .collect();
+        let cluster_size = self.boot.cluster_size();
+        Ok(FatFile::new(&mut self.handle, extents, cluster_size, entry.size as u64))
+    }
+}
+
+impl<H: Read + Seek> ::std::fmt::Debug for FatFs<H> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.debug_struct("FatFs").field("boot", &self.boot).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::io::Read;
+
+    use super::FatFs;
+    use super::FatType;
+
+    /// Build a small FAT12 image containing `EFI/BOOT/BOOTX64.EFI`.
+    fn image() -> Vec<u8> {
+        // 512-byte sectors, 1 sector per cluster, 1 reserved sector,
+        // 2 FATs of 1 sector, 16 root entries, 64 sectors
+        let mut img = vec![0; 64 * 512];
+        img[..3].copy_from_slice(b"\xEB\x3C\x90");
+        img[11..24].copy_from_slice(b"\x00\x02\x01\x01\x00\x02\x10\x00\x40\x00\xf8\x01\x00");
+        img[510..512].copy_from_slice(b"\x55\xAA");
+
+        // FAT: cluster 2 (EFI), 3 (BOOT), 4 -> 5 (BOOTX64.EFI)
+        img[512..521].copy_from_slice(b"\xF8\xFF\xFF\xFF\xFF\xFF\x05\xF0\xFF");
+
+        let entry = |img: &mut Vec<u8>, offset: usize, name: &[u8], attr: u8, cluster: u8, size: u32| {
+            img[offset..offset + 11].copy_from_slice(name);
+            img[offset + 11] = attr;
+            img[offset + 26] = cluster;
+            img[offset + 28..offset + 32].copy_from_slice(&size.to_le_bytes());
+        };
+
+        // root directory at sector 3, data region at sector 4
+        entry(&mut img, 3 * 512, b"EFI        ", 0x10, 2, 0);
+        entry(&mut img, 4 * 512, b".          ", 0x10, 2, 0);
+        entry(&mut img, 4 * 512 + 32, b"..         ", 0x10, 0, 0);
+        entry(&mut img, 4 * 512 + 64, b"BOOT       ", 0x10, 3, 0);
+        entry(&mut img, 5 * 512, b"BOOTX64 EFI", 0x20, 4, 600);
+        for (i, byte) in img[6 * 512..6 * 512 + 600].iter_mut().enumerate() {
+            *byte = (i % 251) as u8;
+        }
+
+        img
+    }
+
+    #[test]
+    fn test_read_dir() {
+        let mut fat = FatFs::from_buffer(image()).unwrap();
+        assert_eq!(fat.fat_type(), FatType::Fat12);
+
+        let root = fat.read_dir("/").unwrap();
+        assert_eq!(root.len(), 1);
+        assert_eq!(root[0].name(), "EFI");
+        assert!(root[0].is_dir());
+
+        let boot = fat.read_dir("/EFI/BOOT").unwrap();
+        assert_eq!(boot.len(), 1);
+        assert_eq!(boot[0].path(), ::std::path::Path::new("/EFI/BOOT/BOOTX64.EFI"));
+        assert_eq!(boot[0].len(), 600);
+    }
+
+    #[test]
+    fn test_open_file() {
+        let mut fat = FatFs::from_buffer(image()).unwrap();
+        assert!(fat.is_file("efi/boot/bootx64.efi"));
+        assert!(fat.is_dir("EFI/BOOT/.."));
+        assert!(!fat.exists("EFI/BOOT/GRUBX64.EFI"));
+
+        let mut content = Vec::new();
+        fat.open_file("EFI/BOOT/BOOTX64.EFI")
+            .unwrap()
+            .read_to_end(&mut content)
+            .unwrap();
+        let expected = (0..600).map(|i| (i % 251) as u8).collect::<Vec<u8>>();
+        assert_eq!(content, expected);
+
+        assert!(fat.open_file("EFI").is_err());
+        assert!(fat.read_dir("EFI/BOOT/BOOTX64.EFI").is_err());
+    }
+
+    #[test]
+    fn test_fat_size() {
+        let mut img = image();
+        img[22..24].copy_from_slice(b"\xFF\xFF");
+        assert!(FatFs::from_buffer(img).is_err());
+    }
+}
@@ -1,4 +1,9 @@
-#[derive(Debug)]
+use nom::le_u32;
+
+use super::super::eltorito::EL_TORITO;
+
+/// A Boot Record, giving a system the information it needs to boot.
+#[derive(Clone, Debug)]
 pub struct BootRecord {
     version: u8,
     system_identifier: String,
@@ -7,12 +12,42 @@ pub struct BootRecord {
 }
 
 impl BootRecord {
-    pub fn parse<'a>(bytes: &[u8]) -> ::error::Result<Self> {
+    pub fn parse(bytes: &[u8]) -> ::error::Result<Self> {
         match parser::boot_record(bytes) {
             Ok((_, record)) => Ok(record),
             Err(err) => Err(err.into()),
         }
     }
+
+    /// Get the version of the descriptor.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Get the identifier of the system that can boot from the volume.
+    pub fn system_identifier(&self) -> &str {
+        &self.system_identifier
+    }
+
+    /// Get the identifier of the boot system.
+    pub fn boot_identifier(&self) -> &str {
+        &self.boot_identifier
+    }
+
+    /// Get the system use area of the boot record.
+    pub fn system_use(&self) -> &[u8] {
+        &self.system_use
+    }
+
+    /// Get the location of the El Torito boot catalog, if this boot record
+    /// follows the El Torito specification.
+    pub fn boot_catalog(&self) -> Option<u32> {
+        if self.system_identifier == EL_TORITO {
+            le_u32(&self.system_use).ok().map(|(_, location)| location)
+        } else {
+            None
+        }
+    }
 }
 
 mod parser {
@@ -20,19 +55,19 @@ mod parser {
     use nom::be_u8;
     use super::BootRecord;
 
-    #[cfg_attr(rustfmt, rustfmt_skip)]
+    #[rustfmt::skip]
     named!(pub boot_record(&[u8]) -> BootRecord,
         do_parse!(
                      tag!(b"\0")                                           >>
                      tag!(b"CD001")                                        >>
             version: be_u8                                                 >>
-            sys_id:  map_res!(null_terminated!(32), ::std::str::from_utf8) >>
-            boot_id: map_res!(null_terminated!(32), ::std::str::from_utf8) >>
+            sys_id:  null_terminated!(32)                                  >>
+            boot_id: null_terminated!(32)                                  >>
             sys_use: take!(1977)                                           >>
                      (BootRecord {
                          version,
-                         system_identifier: sys_id.to_owned(),
-                         boot_identifier: boot_id.to_owned(),
+                         system_identifier: String::from_utf8_lossy(sys_id).into_owned(),
+                         boot_identifier: String::from_utf8_lossy(boot_id).into_owned(),
                          system_use: sys_use.to_vec(),
                      })
         )
@@ -50,6 +85,17 @@ mod parser {
             assert_eq!(record.version, 1);
             assert_eq!(record.system_identifier, "EL TORITO SPECIFICATION");
         }
+
+        #[test]
+        fn test_boot_catalog() {
+            let mut buf = Vec::new();
+            buf.extend(b"\x00CD001\x01EL TORITO SPECIFICATION");
+            buf.resize(0x47, 0);
+            buf.extend(b"\x13\x00\x00\x00");
+            buf.resize(2048, 0);
+            let record = super::super::BootRecord::parse(&buf).unwrap();
+            assert_eq!(record.boot_catalog(), Some(0x13));
+        }
     }
 
 }
@@ -1,33 +1,178 @@
 mod boot;
 mod terminator;
+mod partition;
 mod primary;
+mod supplementary;
 
 pub use self::boot::BootRecord;
 pub use self::terminator::SetTerminator;
+pub use self::partition::VolumePartitionDescriptor;
 pub use self::primary::PrimaryVolumeDescriptor;
+pub use self::primary::VolumeInfo;
+pub use self::supplementary::SupplementaryVolumeDescriptor;
+
+pub(in iso) use self::primary::parser::optional_datetime;
 
 use nom::Err::Incomplete;
 use nom::Needed::Size;
 
 use super::constants::SECTOR_SIZE;
 
-#[derive(Debug)]
+/// A volume descriptor of the volume descriptor set.
+#[derive(Clone, Debug)]
 pub enum VolumeDescriptor {
     Boot(BootRecord),
     Terminator(SetTerminator),
-    Primary(PrimaryVolumeDescriptor),
+    Primary(Box<PrimaryVolumeDescriptor>),
+    Supplementary(Box<SupplementaryVolumeDescriptor>),
+    /// An Enhanced Volume Descriptor, i.e. a supplementary descriptor of
+    /// version 2.
+    Enhanced(Box<SupplementaryVolumeDescriptor>),
+    Partition(Box<VolumePartitionDescriptor>),
+    /// A descriptor of a type not described by ISO-9660, kept as the raw
+    /// bytes of its sector.
+    Unknown { descriptor_type: u8, raw: Vec<u8> },
 }
 
 impl VolumeDescriptor {
+    /// Parse a volume descriptor from the bytes of a sector.
+    ///
+    /// # Errors
+    ///
+    /// * [`UnknownDescriptorType`](../error/enum.ErrorKind.html#variant.UnknownDescriptorType)
+    ///   when the sector does not hold a volume descriptor
+    ///
+    /// Descriptors of High Sierra volumes are recognized by their `CDROM`
+    /// standard identifier.
     pub fn parse(bytes: &[u8]) -> ::error::Result<Self> {
         use self::VolumeDescriptor::*;
 
+        if bytes.get(9..14) == Some(b"CDROM") {
+            return Self::parse_high_sierra(bytes);
+        }
+
         Ok(match bytes.first() {
             None => bail!(Incomplete::<&[u8]>(Size(SECTOR_SIZE as usize))),
             Some(&0x00) => Boot(BootRecord::parse(bytes)?),
-            Some(&0x01) => Primary(PrimaryVolumeDescriptor::parse(bytes)?),
+            Some(&0x01) => Primary(Box::new(PrimaryVolumeDescriptor::parse(bytes)?)),
+            Some(&0x02) => {
+                let svd = SupplementaryVolumeDescriptor::parse(bytes)?;
+                if svd.is_enhanced() {
+                    Enhanced(Box::new(svd))
+                } else {
+                    Supplementary(Box::new(svd))
+                }
+            }
+            Some(&0x03) => Partition(Box::new(VolumePartitionDescriptor::parse(bytes)?)),
             Some(&0xFF) => Terminator(SetTerminator::parse(bytes)?),
-            Some(&othr) => bail!(::error::ErrorKind::UnknownDescriptorType(othr)),
+            Some(&othr) => match bytes.get(..SECTOR_SIZE as usize) {
+                Some(raw) if &raw[1..6] == b"CD001" => Unknown {
+                    descriptor_type: othr,
+                    raw: raw.to_vec(),
+                },
+                _ => bail!(::error::ErrorKind::UnknownDescriptorType(othr)),
+            },
+        })
+    }
+
+    /// Parse a volume descriptor of a High Sierra volume, which records its
+    /// type after the number of its logical block.
+    fn parse_high_sierra(bytes: &[u8]) -> ::error::Result<Self> {
+        use self::VolumeDescriptor::*;
+
+        Ok(match bytes[8] {
+            0x01 => Primary(Box::new(PrimaryVolumeDescriptor::parse_high_sierra(bytes)?)),
+            0xFF => Terminator(SetTerminator::parse_high_sierra(bytes)?),
+            othr => match bytes.get(..SECTOR_SIZE as usize) {
+                Some(raw) => Unknown {
+                    descriptor_type: othr,
+                    raw: raw.to_vec(),
+                },
+                None => bail!(Incomplete::<&[u8]>(Size(SECTOR_SIZE as usize))),
+            },
         })
     }
+
+    /// Get the type code of the descriptor.
+    pub fn descriptor_type(&self) -> u8 {
+        match *self {
+            VolumeDescriptor::Boot(_) => 0x00,
+            VolumeDescriptor::Primary(_) => 0x01,
+            VolumeDescriptor::Supplementary(_) | VolumeDescriptor::Enhanced(_) => 0x02,
+            VolumeDescriptor::Partition(_) => 0x03,
+            VolumeDescriptor::Terminator(_) => 0xFF,
+            VolumeDescriptor::Unknown { descriptor_type, .. } => descriptor_type,
+        }
+    }
+}
+
+/// An iterator over the volume descriptors of an [`IsoFs`], with the
+/// number of the sector they were read from.
+///
+/// [`IsoFs`]: struct.IsoFs.html
+pub struct Descriptors<'a> {
+    inner: ::std::slice::Iter<'a, (u32, VolumeDescriptor)>,
+}
+
+impl<'a> Descriptors<'a> {
+    pub(in iso) fn new(descriptors: &'a [(u32, VolumeDescriptor)]) -> Self {
+        Self {
+            inner: descriptors.iter(),
+        }
+    }
+}
+
+impl<'a> Iterator for Descriptors<'a> {
+    type Item = (u32, &'a VolumeDescriptor);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(sector, descriptor)| (*sector, descriptor))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::VolumeDescriptor;
+
+    #[test]
+    fn test_unknown() {
+        let mut buf = vec![0; 2048];
+        buf[..7].copy_from_slice(b"\x04CD001\x01");
+        match VolumeDescriptor::parse(&buf).unwrap() {
+            VolumeDescriptor::Unknown { descriptor_type, raw } => {
+                assert_eq!(descriptor_type, 4);
+                assert_eq!(raw, buf);
+            }
+            other => panic!("unexpected descriptor: {:?}", other),
+        }
+
+        buf[1] = b'X';
+        assert!(VolumeDescriptor::parse(&buf).is_err());
+    }
+
+    #[test]
+    fn test_enhanced() {
+        let mut buf = vec![0; 2048];
+        buf[..7].copy_from_slice(b"\x02CD001\x02");
+        buf[128..132].copy_from_slice(b"\x00\x08\x08\x00");
+        buf[156..190].copy_from_slice(
+            b"\"\0\x14\0\0\0\0\0\0\x14\0\x08\0\0\0\0\x08\0v\x04\x1a\x114\x1e\x08\x02\0\0\x01\0\0\x01\x01\0",
+        );
+        buf[881] = 2;
+        match VolumeDescriptor::parse(&buf).unwrap() {
+            VolumeDescriptor::Enhanced(evd) => assert_eq!(evd.joliet_level(), None),
+            other => panic!("unexpected descriptor: {:?}", other),
+        }
+
+        buf[6] = 1;
+        match VolumeDescriptor::parse(&buf).unwrap() {
+            VolumeDescriptor::Supplementary(svd) => assert!(!svd.is_enhanced()),
+            other => panic!("unexpected descriptor: {:?}", other),
+        }
+    }
 }
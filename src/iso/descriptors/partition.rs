@@ -0,0 +1,80 @@
+/// A Volume Partition Descriptor, describing a partition of the volume
+/// space that is not part of the file structure of the volume.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VolumePartitionDescriptor {
+    pub version: u8,
+    /// The system that can use the partition.
+    pub system_id: String,
+    /// The label of the partition.
+    pub partition_id: String,
+    /// The first logical block of the partition.
+    pub location: u32,
+    /// The number of logical blocks of the partition.
+    pub size: u32,
+    pub system_use: Vec<u8>,
+}
+
+impl VolumePartitionDescriptor {
+    pub fn parse(bytes: &[u8]) -> ::error::Result<Self> {
+        match parser::vpd(bytes) {
+            Ok((_, vpd)) => Ok(vpd),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+mod parser {
+
+    use nom::be_u8;
+
+    use utils::charsets::padded;
+    use utils::parsers::both_u32;
+
+    use super::VolumePartitionDescriptor;
+
+    #[rustfmt::skip]
+    named!(pub vpd(&[u8]) -> VolumePartitionDescriptor,
+        do_parse!(
+                            tag!(b"\x03")                      >>
+                            tag!(b"CD001")                     >>
+            version:        be_u8                              >>
+                            take!(1)                           >>
+            system_id:      take!(32)                          >>
+            partition_id:   take!(32)                          >>
+            location:       both_u32                           >>
+            size:           both_u32                           >>
+            system_use:     take!(1960)                        >>
+                            (VolumePartitionDescriptor {
+                                version,
+                                system_id: padded(system_id),
+                                partition_id: padded(partition_id),
+                                location,
+                                size,
+                                system_use: system_use.to_vec(),
+                            })
+        )
+    );
+
+    #[cfg(test)]
+    mod tests {
+
+        use super::VolumePartitionDescriptor;
+
+        #[test]
+        fn test_vpd() {
+            let mut buf = vec![b' '; 2048];
+            buf[..8].copy_from_slice(b"\x03CD001\x01\x00");
+            buf[8..13].copy_from_slice(b"LINUX");
+            buf[40..44].copy_from_slice(b"DATA");
+            buf[72..88].copy_from_slice(b"\x20\0\0\0\0\0\0\x20\x10\0\0\0\0\0\0\x10");
+            let vpd = VolumePartitionDescriptor::parse(&buf).unwrap();
+            assert_eq!(vpd.version, 1);
+            assert_eq!(vpd.system_id, "LINUX");
+            assert_eq!(vpd.partition_id, "DATA");
+            assert_eq!(vpd.location, 0x20);
+            assert_eq!(vpd.size, 0x10);
+            assert_eq!(vpd.system_use.len(), 1960);
+        }
+    }
+
+}
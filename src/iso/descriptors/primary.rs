@@ -1,9 +1,67 @@
+use chrono::DateTime;
+use chrono::offset::FixedOffset;
+
+use super::super::path_table::PathTableLocations;
 use super::super::record::Record;
 
-#[derive(Debug)]
+/// The information about a volume recorded in its Primary Volume Descriptor.
+///
+/// Identifiers are decoded and stripped of their padding. Identifiers
+/// starting with `_` name a file of the root directory holding the actual
+/// information, as do the copyright, abstract and bibliographic file
+/// identifiers.
+///
+/// Dates are `None` when they are unset or invalid: the `raw_*_date` fields
+/// keep the recorded bytes, which are zero digits or null bytes for unset
+/// dates.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VolumeInfo {
+    /// The system that can act upon the system area of the volume.
+    pub system_id: String,
+    /// The label of the volume.
+    pub volume_id: String,
+    /// The number of logical blocks of the volume.
+    pub volume_space_size: u32,
+    /// The number of volumes of the volume set.
+    pub volume_set_size: u16,
+    /// The number of the volume in its volume set, starting at 1.
+    pub volume_sequence_number: u16,
+    /// The size of a logical block, in bytes.
+    pub logical_block_size: u16,
+    /// The label of the volume set.
+    pub volume_set_id: String,
+    pub publisher_id: String,
+    pub preparer_id: String,
+    pub application_id: String,
+    pub copyright_file_id: String,
+    pub abstract_file_id: String,
+    pub bibliographic_file_id: String,
+    pub creation_date: Option<DateTime<FixedOffset>>,
+    pub modification_date: Option<DateTime<FixedOffset>>,
+    /// The date after which the volume should be considered obsolete.
+    pub expiration_date: Option<DateTime<FixedOffset>>,
+    /// The date after which the volume may be used.
+    pub effective_date: Option<DateTime<FixedOffset>>,
+    /// The creation date as recorded.
+    pub raw_creation_date: Vec<u8>,
+    /// The modification date as recorded.
+    pub raw_modification_date: Vec<u8>,
+    /// The expiration date as recorded.
+    pub raw_expiration_date: Vec<u8>,
+    /// The effective date as recorded.
+    pub raw_effective_date: Vec<u8>,
+}
+
+/// The Primary Volume Descriptor, describing the volume and its primary
+/// directory hierarchy.
+#[derive(Clone, Debug)]
 pub struct PrimaryVolumeDescriptor {
-    pub root: Record,
+    pub version: u8,
+    pub(in iso) root: Record,
     pub block_size: u16,
+    pub path_tables: PathTableLocations,
+    pub info: VolumeInfo,
+    pub(in iso) high_sierra: bool,
 }
 
 impl PrimaryVolumeDescriptor {
@@ -13,51 +71,162 @@ impl PrimaryVolumeDescriptor {
             Err(err) => Err(err.into()),
         }
     }
+
+    /// Parse the Standard File Structure Volume Descriptor of a High Sierra
+    /// volume, which is the equivalent of the Primary Volume Descriptor.
+    pub fn parse_high_sierra(bytes: &[u8]) -> ::error::Result<Self> {
+        match parser::hs_pvd(bytes) {
+            Ok((_, pvd)) => Ok(pvd),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Check whether the descriptor was read from a High Sierra volume.
+    pub fn is_high_sierra(&self) -> bool {
+        self.high_sierra
+    }
 }
 
-mod parser {
+pub(super) mod parser {
 
     use btoi::btou;
 
-    use nom::be_u8;
-
     use chrono::DateTime;
-    use chrono::TimeZone;
+    use chrono::NaiveDate;
     use chrono::offset::FixedOffset;
 
+    use nom::be_u32;
+    use nom::be_u8;
+    use nom::le_u32;
+
+    use utils::charsets::Charset;
     use utils::parsers::both_u16;
     use utils::parsers::both_u32;
 
+    use utils::charsets::padded;
+
+    use super::PathTableLocations;
     use super::PrimaryVolumeDescriptor;
+    use super::VolumeInfo;
+    use super::super::super::record::Format;
     use super::super::super::record::parser::record;
+    use super::super::super::record::parser::with_offset;
+    use super::super::super::path_table::locations;
+
+    /// Decode a 17-byte date, if it is set and valid.
+    ///
+    /// Dates made of zero digits (or of null bytes) are unset, and have an
+    /// invalid month and day anyway. High Sierra dates are only 16 bytes
+    /// long, and have no timezone: they are decoded as GMT.
+    fn long_datetime(buff: &[u8]) -> Option<DateTime<FixedOffset>> {
+        let year = btou::<i32>(&buff[..4]).ok()?;
+        let fields = buff[4..16]
+            .chunks(2)
+            .map(|digits| btou::<u32>(digits).ok())
+            .collect::<Option<Vec<u32>>>()?;
+        let local = NaiveDate::from_ymd_opt(year, fields[0], fields[1])?
+            .and_hms_milli_opt(fields[2], fields[3], fields[4], fields[5] * 10)?;
+        with_offset(local, buff.get(16).map(|&tz| tz as i8).unwrap_or(0))
+    }
+
+    #[rustfmt::skip]
+    named!(pub datetime(&[u8]) -> DateTime<FixedOffset>,
+        map_opt!(take!(17), long_datetime)
+    );
+
+    // Parse a 17-byte date, giving `None` if it is unset or invalid.
+    #[rustfmt::skip]
+    named!(pub optional_datetime(&[u8]) -> Option<DateTime<FixedOffset>>,
+        map!(take!(17), long_datetime)
+    );
 
-    #[cfg_attr(rustfmt, rustfmt_skip)]
-    named!(datetime(&[u8]) -> DateTime<FixedOffset>,
-        // TODO: finer-grained parser
-        // FIXME: no chrono panic
+    #[rustfmt::skip]
+    named_args!(hs_locations(size: u32)<PathTableLocations>,
         do_parse!(
-            year:   map_res!(take!(4), btou::<i32>) >>
-            month:  map_res!(take!(2), btou::<u32>) >>
-            day:    map_res!(take!(2), btou::<u32>) >>
-            hour:   map_res!(take!(2), btou::<u32>) >>
-            min:    map_res!(take!(2), btou::<u32>) >>
-            sec:    map_res!(take!(2), btou::<u32>) >>
-            hun:    map_res!(take!(2), btou::<u32>) >>
-            tz:     be_u8                           >>
-                    (
-                        FixedOffset::east((tz as i32 - 48) * 900)
-                            .ymd(year, month, day)
-                            .and_hms_milli(hour, min, sec, hun*10)
-                    )
+            l_table:          le_u32                           >>
+            optional_l_table: le_u32                           >>
+                              take!(8)                         >>
+            m_table:          be_u32                           >>
+            optional_m_table: be_u32                           >>
+                              take!(8)                         >>
+                              (PathTableLocations {
+                                  size,
+                                  l_table,
+                                  optional_l_table,
+                                  m_table,
+                                  optional_m_table,
+                              })
+        )
+    );
+
+    #[rustfmt::skip]
+    named!(pub hs_pvd(&[u8]) -> PrimaryVolumeDescriptor,
+        do_parse!(
+                            take!(8)                           >>
+                            tag!(b"\x01")                      >>
+                            tag!(b"CDROM")                     >>
+            version:        be_u8                              >>
+                            take!(1)                           >>
+            system_id:      take!(32)                          >>
+            volume_id:      take!(32)                          >>
+                            take!(8)                           >>
+            space_size:     both_u32                           >>
+                            take!(32)                          >>
+            set_size:       both_u16                           >>
+            seq_number:     both_u16                           >>
+            block_size:     both_u16                           >>
+            pt_size:        both_u32                           >>
+            path_tables:    apply!(hs_locations, pt_size)      >>
+            root:           apply!(record, Format::HighSierra(Charset::default())) >>
+            set_id:         take!(128)                         >>
+            pub_id:         take!(128)                         >>
+            prep_id:        take!(128)                         >>
+            app_id:         take!(128)                         >>
+            copyr_file:     take!(32)                          >>
+            abstract_file:  take!(32)                          >>
+            creattime:      take!(16)                          >>
+            modifstime:     take!(16)                          >>
+            expirtime:      take!(16)                          >>
+            effectime:      take!(16)                          >>
+                            (PrimaryVolumeDescriptor {
+                                version,
+                                root,
+                                block_size,
+                                path_tables,
+                                info: VolumeInfo {
+                                    system_id: padded(system_id),
+                                    volume_id: padded(volume_id),
+                                    volume_space_size: space_size,
+                                    volume_set_size: set_size,
+                                    volume_sequence_number: seq_number,
+                                    logical_block_size: block_size,
+                                    volume_set_id: padded(set_id),
+                                    publisher_id: padded(pub_id),
+                                    preparer_id: padded(prep_id),
+                                    application_id: padded(app_id),
+                                    copyright_file_id: padded(copyr_file),
+                                    abstract_file_id: padded(abstract_file),
+                                    bibliographic_file_id: String::new(),
+                                    creation_date: long_datetime(creattime),
+                                    modification_date: long_datetime(modifstime),
+                                    expiration_date: long_datetime(expirtime),
+                                    effective_date: long_datetime(effectime),
+                                    raw_creation_date: creattime.to_vec(),
+                                    raw_modification_date: modifstime.to_vec(),
+                                    raw_expiration_date: expirtime.to_vec(),
+                                    raw_effective_date: effectime.to_vec(),
+                                },
+                                high_sierra: true,
+                            })
         )
     );
 
-    #[cfg_attr(rustfmt, rustfmt_skip)]
+    #[rustfmt::skip]
     named!(pub pvd(&[u8]) -> PrimaryVolumeDescriptor,
         do_parse!(
                             tag!(b"\x01")                      >>
                             tag!(b"CD001")                     >>
-            version:        tag!(b"\x01")                      >>
+            version:        be_u8                              >>
                             tag!(b"\x00")                      >>
             system_id:      take!(32)                          >>
             volume_id:      take!(32)                          >>
@@ -68,29 +237,53 @@ mod parser {
             seq_number:     both_u16                           >>
             block_size:     both_u16                           >>
             pt_size:        both_u32                           >>
-                            take!(16)                          >>
-            root:           record                             >>
+            path_tables:    apply!(locations, pt_size)         >>
+            root:           apply!(record, Format::Iso(Charset::default())) >>
             set_id:         take!(128)                         >>
             pub_id:         take!(128)                         >>
             prep_id:        take!(128)                         >>
             app_id:         take!(128)                         >>
-            copyr_file:     take!(38)                          >>
-            abstract_file:  take!(36)                          >>
+            copyr_file:     take!(37)                          >>
+            abstract_file:  take!(37)                          >>
             biblio_file:    take!(37)                          >>
-            creattime:      datetime                           >>
-            modifstime:     datetime                           >>
-
-            // TODO:        optional dates (all zeros for None)
+            creattime:      take!(17)                          >>
+            modifstime:     take!(17)                          >>
             expirtime:      take!(17)                          >>
             effectime:      take!(17)                          >>
 
-            fs_version:     tag!(b"\x01")                      >>
-                            tag!(b"\0")                        >>
+            fs_version:     be_u8                              >>
+                            take!(1)                           >>
             app_used:       take!(512)                         >>
             reserved:       take!(653)                         >>
                             (PrimaryVolumeDescriptor {
+                                version,
                                 root,
                                 block_size,
+                                path_tables,
+                                info: VolumeInfo {
+                                    system_id: padded(system_id),
+                                    volume_id: padded(volume_id),
+                                    volume_space_size: space_size,
+                                    volume_set_size: set_size,
+                                    volume_sequence_number: seq_number,
+                                    logical_block_size: block_size,
+                                    volume_set_id: padded(set_id),
+                                    publisher_id: padded(pub_id),
+                                    preparer_id: padded(prep_id),
+                                    application_id: padded(app_id),
+                                    copyright_file_id: padded(copyr_file),
+                                    abstract_file_id: padded(abstract_file),
+                                    bibliographic_file_id: padded(biblio_file),
+                                    creation_date: long_datetime(creattime),
+                                    modification_date: long_datetime(modifstime),
+                                    expiration_date: long_datetime(expirtime),
+                                    effective_date: long_datetime(effectime),
+                                    raw_creation_date: creattime.to_vec(),
+                                    raw_modification_date: modifstime.to_vec(),
+                                    raw_expiration_date: expirtime.to_vec(),
+                                    raw_effective_date: effectime.to_vec(),
+                                },
+                                high_sierra: false,
                             })
         )
     );
@@ -112,7 +305,76 @@ mod parser {
             assert_eq!(dt.minute(), 30);
             assert_eq!(dt.second(), 12);
             assert_eq!(dt.nanosecond(), 30_000_000);
-            // assert_eq!(dt.tz, 0x0A);
+            assert_eq!(dt.offset().local_minus_utc(), 150 * 60);
+        }
+
+        #[test]
+        fn test_datetime_invalid() {
+            let (_, dt) = super::datetime(b"1996111316301203\xD0").unwrap();
+            assert_eq!(dt.offset().local_minus_utc(), -12 * 3600);
+            let (_, dt) = super::datetime(b"1996111316301203\x34").unwrap();
+            assert_eq!(dt.offset().local_minus_utc(), 13 * 3600);
+            assert!(super::datetime(b"1996111316301203\x35").is_err());
+            assert!(super::datetime(b"1996133116301203\x00").is_err());
+            assert!(super::datetime(b"1996023016301203\x00").is_err());
+            assert!(super::datetime(b"19961113  301203\x00").is_err());
+            assert!(super::datetime(b"1996111316301203").is_err());
+            assert_eq!(super::optional_datetime(b"19961113  301203\x00").unwrap().1, None);
+            assert_eq!(super::optional_datetime(&[0; 17]).unwrap().1, None);
+        }
+
+        #[test]
+        fn test_pvd() {
+            let mut buf = vec![b' '; 2048];
+            buf[..8].copy_from_slice(b"\x01CD001\x01\x00");
+            buf[8..13].copy_from_slice(b"LINUX");
+            buf[40..45].copy_from_slice(b"LABEL");
+            buf[72..156].iter_mut().for_each(|b| *b = 0);
+            buf[80..88].copy_from_slice(b"\xf3\x01\0\0\0\0\x01\xf3");
+            buf[120..132].copy_from_slice(b"\x01\0\0\x01\x02\0\0\x02\0\x08\x08\0");
+            buf[156..190].copy_from_slice(
+                b"\"\0\x14\0\0\0\0\0\0\x14\0\x08\0\0\0\0\x08\0v\x04\x1a\x114\x1e\x08\x02\0\0\x01\0\0\x01\x01\0",
+            );
+            buf[318..325].copy_from_slice(b"_PUBLIS");
+            buf[702..711].copy_from_slice(b"COPYRIGHT");
+            buf[739..747].copy_from_slice(b"ABSTRACT");
+            buf[776..780].copy_from_slice(b"BIBL");
+            buf[813..830].copy_from_slice(b"2018042617530812\x08");
+            buf[830..847].copy_from_slice(b"0000000000000000\0");
+            buf[847..864].copy_from_slice(b"2018133117530812\x08");
+            buf[864..881].copy_from_slice(b"2018042617530812\x08");
+            buf[881..883].copy_from_slice(b"\x01\0");
+
+            let (_, pvd) = super::pvd(&buf).unwrap();
+            let info = pvd.info;
+            assert_eq!(info.system_id, "LINUX");
+            assert_eq!(info.volume_id, "LABEL");
+            assert_eq!(info.volume_space_size, 499);
+            assert_eq!(info.volume_set_size, 1);
+            assert_eq!(info.volume_sequence_number, 2);
+            assert_eq!(info.logical_block_size, 2048);
+            assert_eq!(info.volume_set_id, "");
+            assert_eq!(info.publisher_id, "_PUBLIS");
+            assert_eq!(info.copyright_file_id, "COPYRIGHT");
+            assert_eq!(info.abstract_file_id, "ABSTRACT");
+            assert_eq!(info.bibliographic_file_id, "BIBL");
+            assert_eq!(info.creation_date.unwrap().year(), 2018);
+            assert_eq!(info.modification_date, None);
+            assert_eq!(info.expiration_date, None);
+            assert_eq!(info.effective_date, info.creation_date);
+
+            // unset and invalid dates are both `None`, but keep their raw bytes
+            assert_eq!(info.raw_modification_date, b"0000000000000000\0");
+            assert_eq!(info.raw_expiration_date, b"2018133117530812\x08");
+            assert_eq!(info.raw_effective_date, b"2018042617530812\x08");
+        }
+
+        #[test]
+        fn test_optional_datetime() {
+            let (_, dt) = super::optional_datetime(b"0000000000000000\x00").unwrap();
+            assert_eq!(dt, None);
+            let (_, dt) = super::optional_datetime(b"1996111316301203\n").unwrap();
+            assert!(dt.is_some());
         }
     }
 
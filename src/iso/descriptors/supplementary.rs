@@ -0,0 +1,169 @@
+use super::super::path_table::PathTableLocations;
+use super::super::record::Record;
+
+/// The escape sequences announcing a Joliet hierarchy, by level.
+const JOLIET_ESCAPES: [(&[u8; 3], u8); 3] = [(b"%/@", 1), (b"%/C", 2), (b"%/E", 3)];
+
+/// A Supplementary Volume Descriptor, describing an additional directory
+/// hierarchy, such as a Joliet hierarchy or the hierarchy of relaxed
+/// identifiers of an Enhanced Volume Descriptor.
+#[derive(Clone, Debug)]
+pub struct SupplementaryVolumeDescriptor {
+    pub version: u8,
+    pub flags: u8,
+    pub escape_sequences: Vec<u8>,
+    pub(in iso) root: Record,
+    pub block_size: u16,
+    pub path_tables: PathTableLocations,
+}
+
+impl SupplementaryVolumeDescriptor {
+    pub fn parse(bytes: &[u8]) -> ::error::Result<Self> {
+        match parser::svd(bytes) {
+            Ok((_, svd)) => Ok(svd),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Check whether this descriptor is an Enhanced Volume Descriptor, as
+    /// defined in ISO-9660:1999.
+    pub fn is_enhanced(&self) -> bool {
+        self.version == 2
+    }
+
+    /// Get the Joliet level of this descriptor, if it describes a Joliet
+    /// hierarchy.
+    pub fn joliet_level(&self) -> Option<u8> {
+        JOLIET_ESCAPES
+            .iter()
+            .find(|&&(escape, _)| self.escape_sequences.starts_with(escape))
+            .map(|&(_, level)| level)
+    }
+}
+
+mod parser {
+
+    use nom::be_u8;
+
+    use utils::charsets::Charset;
+    use utils::parsers::both_u16;
+    use utils::parsers::both_u32;
+
+    use super::JOLIET_ESCAPES;
+    use super::SupplementaryVolumeDescriptor;
+    use super::super::super::record::Format;
+    use super::super::super::record::parser::record;
+    use super::super::super::path_table::locations;
+
+    /// Get the format of the hierarchy described by a descriptor with the
+    /// given version and escape sequences.
+    fn root_format(version: u8, escapes: &[u8]) -> Format {
+        if version == 2 {
+            Format::Enhanced(Charset::default())
+        } else if JOLIET_ESCAPES.iter().any(|&(escape, _)| escapes.starts_with(escape)) {
+            Format::Joliet
+        } else {
+            Format::Iso(Charset::default())
+        }
+    }
+
+    #[rustfmt::skip]
+    named!(pub svd(&[u8]) -> SupplementaryVolumeDescriptor,
+        do_parse!(
+                            tag!(b"\x02")                      >>
+                            tag!(b"CD001")                     >>
+            version:        be_u8                              >>
+            flags:          be_u8                              >>
+            system_id:      take!(32)                          >>
+            volume_id:      take!(32)                          >>
+                            take!(8)                           >>
+            space_size:     both_u32                           >>
+            escapes:        take!(32)                          >>
+            set_size:       both_u16                           >>
+            seq_number:     both_u16                           >>
+            block_size:     both_u16                           >>
+            pt_size:        both_u32                           >>
+            path_tables:    apply!(locations, pt_size)         >>
+            root:           apply!(record, root_format(version, escapes)) >>
+            set_id:         take!(128)                         >>
+            pub_id:         take!(128)                         >>
+            prep_id:        take!(128)                         >>
+            app_id:         take!(128)                         >>
+            copyr_file:     take!(37)                          >>
+            abstract_file:  take!(37)                          >>
+            biblio_file:    take!(37)                          >>
+            creattime:      take!(17)                          >>
+            modifstime:     take!(17)                          >>
+            expirtime:      take!(17)                          >>
+            effectime:      take!(17)                          >>
+            fs_version:     be_u8                              >>
+                            take!(1)                           >>
+            app_used:       take!(512)                         >>
+            reserved:       take!(653)                         >>
+                            (SupplementaryVolumeDescriptor {
+                                version,
+                                flags,
+                                escape_sequences: escapes.to_vec(),
+                                root,
+                                block_size,
+                                path_tables,
+                            })
+        )
+    );
+
+    #[cfg(test)]
+    mod tests {
+
+        use super::SupplementaryVolumeDescriptor;
+
+        /// Build a supplementary volume descriptor with the given escapes.
+        fn svd_bytes(escapes: &[u8]) -> Vec<u8> {
+            let mut buf = vec![0; 2048];
+            buf[..7].copy_from_slice(b"\x02CD001\x01");
+            buf[88..88 + escapes.len()].copy_from_slice(escapes);
+            buf[128..132].copy_from_slice(b"\x00\x08\x08\x00");
+            buf[156..190].copy_from_slice(
+                b"\"\0\x14\0\0\0\0\0\0\x14\0\x08\0\0\0\0\x08\0v\x04\x1a\x114\x1e\x08\x02\0\0\x01\0\0\x01\x01\0",
+            );
+            for date in 0..4 {
+                let start = 813 + date * 17;
+                buf[start..start + 16].copy_from_slice(b"2018042617530812");
+            }
+            buf[881] = 1;
+            buf
+        }
+
+        #[test]
+        fn test_svd() {
+            let buf = svd_bytes(b"%/E");
+            let svd = SupplementaryVolumeDescriptor::parse(&buf).unwrap();
+            assert_eq!(svd.version, 1);
+            assert_eq!(svd.block_size, 2048);
+            assert_eq!(svd.root.extent, 0x14);
+            assert!(svd.root.is_dir);
+        }
+
+        #[test]
+        fn test_root_format() {
+            use utils::charsets::Charset;
+            use super::super::super::super::record::Format;
+
+            assert_eq!(super::root_format(1, b"%/E"), Format::Joliet);
+            assert_eq!(super::root_format(1, b""), Format::Iso(Charset::default()));
+            assert_eq!(super::root_format(2, b""), Format::Enhanced(Charset::default()));
+            assert_eq!(super::root_format(2, b"%/E"), Format::Enhanced(Charset::default()));
+        }
+
+        #[test]
+        fn test_joliet_level() {
+            let levels = [(&b"%/@"[..], Some(1)), (b"%/C", Some(2)), (b"%/E", Some(3))];
+            for &(escapes, level) in levels.iter() {
+                let svd = SupplementaryVolumeDescriptor::parse(&svd_bytes(escapes)).unwrap();
+                assert_eq!(svd.joliet_level(), level);
+            }
+            let svd = SupplementaryVolumeDescriptor::parse(&svd_bytes(b"")).unwrap();
+            assert_eq!(svd.joliet_level(), None);
+        }
+    }
+
+}
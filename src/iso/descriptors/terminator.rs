@@ -1,4 +1,5 @@
-#[derive(Debug)]
+/// The Volume Descriptor Set Terminator, ending the volume descriptor set.
+#[derive(Clone, Debug)]
 pub struct SetTerminator {
     version: u8,
 }
@@ -10,13 +11,27 @@ impl SetTerminator {
             Err(err) => Err(err.into()),
         }
     }
+
+    /// Parse the terminator of the volume descriptor set of a High Sierra
+    /// volume.
+    pub fn parse_high_sierra(bytes: &[u8]) -> ::error::Result<Self> {
+        match parser::hs_terminator(bytes) {
+            Ok((_, term)) => Ok(term),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Get the version of the descriptor.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
 }
 
 mod parser {
     use nom::be_u8;
     use super::SetTerminator;
 
-    #[cfg_attr(rustfmt, rustfmt_skip)]
+    #[rustfmt::skip]
     named!(pub terminator(&[u8]) -> SetTerminator,
         do_parse!(
                      tag!(b"\xFF")  >>
@@ -29,6 +44,19 @@ mod parser {
         )
     );
 
+    #[rustfmt::skip]
+    named!(pub hs_terminator(&[u8]) -> SetTerminator,
+        do_parse!(
+                     take!(8)       >>
+                     tag!(b"\xFF")  >>
+                     tag!(b"CDROM") >>
+            version: be_u8          >>
+                     (SetTerminator {
+                         version
+                     })
+        )
+    );
+
     #[cfg(test)]
     mod tests {
 
@@ -43,6 +71,14 @@ mod parser {
             assert_eq!(record.version, 1);
         }
 
+        #[test]
+        fn test_parse_high_sierra() {
+            let mut buf = vec![0; 2048];
+            buf[8..15].copy_from_slice(b"\xFFCDROM\x01");
+            let record = SetTerminator::parse_high_sierra(&buf).unwrap();
+            assert_eq!(record.version, 1);
+        }
+
     }
 
 }
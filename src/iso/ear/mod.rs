@@ -0,0 +1,65 @@
+//! Extended Attribute Records.
+//!
+//! A file or directory may be preceded by an Extended Attribute Record in
+//! the first logical blocks of its extent, as declared by the `ear_length`
+//! of its directory record. The file data only starts after those blocks.
+
+mod parser;
+
+use chrono::DateTime;
+use chrono::offset::FixedOffset;
+
+/// The read and execute bits of an Extended Attribute Record for the owner,
+/// group and other classes, with their POSIX equivalent.
+///
+/// A bit set to *zero* grants the permission, the odd bits being always set.
+const PERMISSIONS: [(u16, u32); 6] = [
+    (1 << 4, 0o400),
+    (1 << 6, 0o100),
+    (1 << 8, 0o040),
+    (1 << 10, 0o010),
+    (1 << 12, 0o004),
+    (1 << 14, 0o001),
+];
+
+/// The contents of an Extended Attribute Record.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtendedAttributes {
+    pub owner: u16,
+    pub group: u16,
+    /// The raw permissions field, see [`mode`](#method.mode).
+    pub permissions: u16,
+    pub creation: Option<DateTime<FixedOffset>>,
+    pub modification: Option<DateTime<FixedOffset>>,
+    pub expiration: Option<DateTime<FixedOffset>>,
+    pub effective: Option<DateTime<FixedOffset>>,
+    pub record_format: u8,
+    pub record_attributes: u8,
+    pub record_length: u16,
+    pub system_id: String,
+    pub system_use: Vec<u8>,
+    pub version: u8,
+    pub application_use: Vec<u8>,
+    pub escape_sequences: Vec<u8>,
+}
+
+impl ExtendedAttributes {
+    pub fn parse(bytes: &[u8]) -> ::error::Result<Self> {
+        match parser::ear(bytes) {
+            Ok((_, ear)) => Ok(ear),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Get the read and execute permissions of the owner, group and other
+    /// classes as POSIX permission bits.
+    ///
+    /// Permissions of the *system* class have no POSIX equivalent and are
+    /// ignored.
+    pub fn mode(&self) -> u32 {
+        PERMISSIONS
+            .iter()
+            .filter(|&&(bit, _)| self.permissions & bit == 0)
+            .fold(0, |mode, &(_, posix)| mode | posix)
+    }
+}
@@ -0,0 +1,86 @@
+use nom::be_u16;
+use nom::be_u8;
+
+use utils::charsets::padded;
+use utils::parsers::both_u16;
+
+use super::super::descriptors::optional_datetime;
+use super::ExtendedAttributes;
+
+#[rustfmt::skip]
+named!(pub ear(&[u8]) -> ExtendedAttributes,
+    do_parse!(
+        owner:             both_u16                          >>
+        group:             both_u16                          >>
+        permissions:       be_u16                            >>
+        creation:          optional_datetime                 >>
+        modification:      optional_datetime                 >>
+        expiration:        optional_datetime                 >>
+        effective:         optional_datetime                 >>
+        record_format:     be_u8                             >>
+        record_attributes: be_u8                             >>
+        record_length:     both_u16                          >>
+        system_id:         take!(32)                         >>
+        system_use:        take!(64)                         >>
+        version:           be_u8                             >>
+        escapes_length:    be_u8                             >>
+                           take!(64)                         >>
+        application_length: both_u16                         >>
+        application_use:   take!(application_length)         >>
+        escape_sequences:  take!(escapes_length)             >>
+                           (ExtendedAttributes {
+                               owner,
+                               group,
+                               permissions,
+                               creation,
+                               modification,
+                               expiration,
+                               effective,
+                               record_format,
+                               record_attributes,
+                               record_length,
+                               system_id: padded(system_id),
+                               system_use: system_use.to_vec(),
+                               version,
+                               application_use: application_use.to_vec(),
+                               escape_sequences: escape_sequences.to_vec(),
+                           })
+    )
+);
+
+#[cfg(test)]
+mod tests {
+
+    use chrono::Datelike;
+
+    use super::super::ExtendedAttributes;
+
+    #[test]
+    fn test_ear() {
+        let mut raw = vec![0; 2048];
+        raw[..10].copy_from_slice(b"\xe8\x03\x03\xe8\x64\x00\x00\x64\xfe\xaa");
+        raw[10..27].copy_from_slice(b"2018042617530812\0");
+        for date in 1..4 {
+            let start = 10 + date * 17;
+            raw[start..start + 17].copy_from_slice(b"0000000000000000\0");
+        }
+        raw[84..89].copy_from_slice(b"LINUX");
+        for x in raw[89..116].iter_mut() {
+            *x = b' ';
+        }
+        raw[180] = 1;
+        raw[246..250].copy_from_slice(b"\x03\x00\x00\x03");
+        raw[250..253].copy_from_slice(b"app");
+
+        let ear = ExtendedAttributes::parse(&raw).unwrap();
+        assert_eq!(ear.owner, 1000);
+        assert_eq!(ear.group, 100);
+        assert_eq!(ear.mode(), 0o540);
+        assert_eq!(ear.creation.unwrap().year(), 2018);
+        assert_eq!(ear.modification, None);
+        assert_eq!(ear.system_id, "LINUX");
+        assert_eq!(ear.version, 1);
+        assert_eq!(ear.application_use, b"app");
+        assert!(ear.escape_sequences.is_empty());
+    }
+}
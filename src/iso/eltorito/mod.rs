@@ -0,0 +1,172 @@
+//! El Torito bootable CD-ROM format.
+
+mod parser;
+
+use super::hybrid::Mbr;
+
+/// The system identifier of a boot record following the El Torito
+/// specification.
+pub(in iso) const EL_TORITO: &str = "EL TORITO SPECIFICATION";
+
+/// The size of the virtual sectors used to count the sectors of an image.
+pub(in iso) const VIRTUAL_SECTOR_SIZE: u64 = 512;
+
+/// The platform a boot image is meant for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Platform {
+    X86,
+    PowerPc,
+    Mac,
+    Efi,
+    Other(u8),
+}
+
+impl From<u8> for Platform {
+    fn from(id: u8) -> Self {
+        match id {
+            0x00 => Platform::X86,
+            0x01 => Platform::PowerPc,
+            0x02 => Platform::Mac,
+            0xEF => Platform::Efi,
+            other => Platform::Other(other),
+        }
+    }
+}
+
+/// The media emulated when booting from a boot image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Emulation {
+    NoEmulation,
+    Floppy1200,
+    Floppy1440,
+    Floppy2880,
+    HardDisk,
+    Other(u8),
+}
+
+impl Emulation {
+    /// Get the size of the emulated media, if it has a fixed size.
+    pub fn media_size(&self) -> Option<u64> {
+        match *self {
+            Emulation::Floppy1200 => Some(1_228_800),
+            Emulation::Floppy1440 => Some(1_474_560),
+            Emulation::Floppy2880 => Some(2_949_120),
+            _ => None,
+        }
+    }
+}
+
+impl From<u8> for Emulation {
+    fn from(media: u8) -> Self {
+        match media & 0x0F {
+            0 => Emulation::NoEmulation,
+            1 => Emulation::Floppy1200,
+            2 => Emulation::Floppy1440,
+            3 => Emulation::Floppy2880,
+            4 => Emulation::HardDisk,
+            other => Emulation::Other(other),
+        }
+    }
+}
+
+/// The validation entry starting a boot catalog.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationEntry {
+    /// The platform of the default entry.
+    pub platform: Platform,
+    /// The identifier of the manufacturer of the CD-ROM.
+    pub id: String,
+}
+
+/// A boot entry, describing a boot image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BootEntry {
+    /// Whether the image is bootable.
+    pub bootable: bool,
+    /// The media emulated by the image.
+    pub emulation: Emulation,
+    /// The memory segment to load the image to (`0` meaning `0x7C0`).
+    pub load_segment: u16,
+    /// The system type, i.e. the partition type of a hard disk image.
+    pub system_type: u8,
+    /// The number of 512-byte virtual sectors loaded by the BIOS.
+    pub sector_count: u16,
+    /// The logical block address of the image.
+    pub load_rba: u32,
+    /// The selection criteria type and vendor criteria of section entries.
+    pub selection_criteria: Option<(u8, Vec<u8>)>,
+}
+
+/// A section of the boot catalog, grouping entries for a platform.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Section {
+    /// The platform of the entries of this section.
+    pub platform: Platform,
+    /// The identifier of the section.
+    pub id: String,
+    /// The boot entries of this section.
+    pub entries: Vec<BootEntry>,
+}
+
+/// An El Torito boot catalog.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BootCatalog {
+    /// The validation entry of the catalog.
+    pub validation: ValidationEntry,
+    /// The default (initial) boot entry.
+    pub default: BootEntry,
+    /// The additional sections of the catalog.
+    pub sections: Vec<Section>,
+}
+
+impl BootCatalog {
+    /// Parse a boot catalog from the bytes of its sectors.
+    ///
+    /// # Errors
+    ///
+    /// * [`InvalidChecksum`](../error/enum.ErrorKind.html#variant.InvalidChecksum)
+    ///   when the checksum of the validation entry is invalid
+    pub fn parse(bytes: &[u8]) -> ::error::Result<Self> {
+        if bytes.len() >= 32 && !parser::checksum(&bytes[..32]) {
+            bail!(::error::ErrorKind::InvalidChecksum);
+        }
+        match parser::catalog(bytes) {
+            Ok((_, catalog)) => Ok(catalog),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Get all the boot entries of the catalog along with their platform,
+    /// starting with the default entry.
+    pub fn entries(&self) -> Vec<(Platform, &BootEntry)> {
+        let mut entries = vec![(self.validation.platform, &self.default)];
+        for section in self.sections.iter() {
+            entries.extend(section.entries.iter().map(|e| (section.platform, e)));
+        }
+        entries
+    }
+}
+
+/// Get the size of a hard disk image from the partition table of its MBR.
+///
+/// Returns `None` if `mbr` does not end with the MBR signature or if all
+/// the partition entries are empty.
+pub(in iso) fn hard_disk_size(mbr: &[u8]) -> Option<u64> {
+    Mbr::parse(mbr)?
+        .partitions
+        .iter()
+        .map(|p| p.start_lba as u64 + p.sector_count as u64)
+        .max()
+        .map(|sectors| sectors * VIRTUAL_SECTOR_SIZE)
+}
+
+/// Get the offset and size of the first partition of a hard disk image.
+///
+/// Returns `None` if `mbr` does not end with the MBR signature or if all
+/// the partition entries are empty.
+pub(in iso) fn hard_disk_partition(mbr: &[u8]) -> Option<(u64, u64)> {
+    Mbr::parse(mbr)?
+        .partitions
+        .first()
+        .map(|p| (p.start_lba as u64 * VIRTUAL_SECTOR_SIZE, p.sector_count as u64 * VIRTUAL_SECTOR_SIZE))
+}
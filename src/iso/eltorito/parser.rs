@@ -0,0 +1,238 @@
+use nom::be_u8;
+use nom::le_u16;
+use nom::le_u32;
+
+use utils::charsets::padded;
+
+use super::BootCatalog;
+use super::BootEntry;
+use super::Emulation;
+use super::Platform;
+use super::Section;
+use super::ValidationEntry;
+
+/// The size of every entry of a boot catalog.
+const ENTRY_SIZE: usize = 32;
+
+/// The flag of the media type byte announcing an extension entry follows.
+const EXTENSION_FOLLOWS: u8 = 0x20;
+
+/// Check the checksum of a validation entry, i.e. that the sum of all its
+/// 16-bit little-endian words is zero.
+pub fn checksum(entry: &[u8]) -> bool {
+    entry
+        .chunks(2)
+        .take(ENTRY_SIZE / 2)
+        .fold(0u16, |acc, word| acc.wrapping_add(word[0] as u16 | (word[1] as u16) << 8))
+        == 0
+}
+
+#[rustfmt::skip]
+named!(validation_entry(&[u8]) -> ValidationEntry,
+    do_parse!(
+                    tag!(b"\x01")                            >>
+        platform:   be_u8                                    >>
+                    take!(2)                                 >>
+        id:         take!(24)                                >>
+                    take!(2)                                 >>
+                    tag!(b"\x55\xAA")                        >>
+                    (ValidationEntry {
+                        platform: Platform::from(platform),
+                        id: padded(id),
+                    })
+    )
+);
+
+#[rustfmt::skip]
+named!(initial_entry(&[u8]) -> BootEntry,
+    do_parse!(
+        indicator:      matching!(0x00 | 0x88)               >>
+        media:          be_u8                                >>
+        load_segment:   le_u16                               >>
+        system_type:    be_u8                                >>
+                        take!(1)                             >>
+        sector_count:   le_u16                               >>
+        load_rba:       le_u32                               >>
+                        take!(20)                            >>
+                        (BootEntry {
+                            bootable: indicator == 0x88,
+                            emulation: Emulation::from(media),
+                            load_segment,
+                            system_type,
+                            sector_count,
+                            load_rba,
+                            selection_criteria: None,
+                        })
+    )
+);
+
+/// Skip the extension entries following a section entry.
+fn extensions(input: &[u8], media: u8) -> ::nom::IResult<&[u8], ()> {
+    let mut input = input;
+    let mut follows = media & EXTENSION_FOLLOWS != 0;
+    while follows {
+        let (rem, flags) = try_parse!(input, do_parse!(
+            tag!(b"\x44") >> flags: be_u8 >> take!(ENTRY_SIZE - 2) >> (flags)
+        ));
+        follows = flags & EXTENSION_FOLLOWS != 0;
+        input = rem;
+    }
+    Ok((input, ()))
+}
+
+#[rustfmt::skip]
+named!(section_entry(&[u8]) -> BootEntry,
+    do_parse!(
+        indicator:      matching!(0x00 | 0x88)               >>
+        media:          be_u8                                >>
+        load_segment:   le_u16                               >>
+        system_type:    be_u8                                >>
+                        take!(1)                             >>
+        sector_count:   le_u16                               >>
+        load_rba:       le_u32                               >>
+        criteria_type:  be_u8                                >>
+        criteria:       take!(19)                            >>
+                        apply!(extensions, media)            >>
+                        (BootEntry {
+                            bootable: indicator == 0x88,
+                            emulation: Emulation::from(media),
+                            load_segment,
+                            system_type,
+                            sector_count,
+                            load_rba,
+                            selection_criteria: Some((criteria_type, criteria.to_vec())),
+                        })
+    )
+);
+
+// parse a section, also returning whether it is the last one of the catalog
+#[rustfmt::skip]
+named!(section(&[u8]) -> (bool, Section),
+    do_parse!(
+        indicator:  matching!(0x90 | 0x91)                   >>
+        platform:   be_u8                                    >>
+        count:      le_u16                                   >>
+        id:         take!(28)                                >>
+        entries:    count!(section_entry, count as usize)    >>
+                    (indicator == 0x91, Section {
+                        platform: Platform::from(platform),
+                        id: padded(id),
+                        entries,
+                    })
+    )
+);
+
+/// Parse a boot catalog, stopping after the final section.
+pub fn catalog(input: &[u8]) -> ::nom::IResult<&[u8], BootCatalog> {
+    let (mut input, validation) = validation_entry(input)?;
+    let (rem, default) = initial_entry(input)?;
+    let mut sections = Vec::new();
+
+    input = rem;
+    loop {
+        match input.first() {
+            Some(&0x90) | Some(&0x91) => {
+                let (rem, (last, section)) = section(input)?;
+                sections.push(section);
+                input = rem;
+                if last {
+                    break;
+                }
+            }
+            // a section that is not the final one may be followed by more
+            // sections in the next sector
+            None if !sections.is_empty() => return Err(::nom::Err::Incomplete(::nom::Needed::Size(1))),
+            _ => break,
+        }
+    }
+
+    Ok((input, BootCatalog { validation, default, sections }))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::super::BootCatalog;
+    use super::super::Emulation;
+    use super::super::Platform;
+
+    /// Build a boot catalog with a BIOS default entry and an EFI section.
+    fn catalog_bytes() -> Vec<u8> {
+        let mut buf = vec![0; 2048];
+        buf[..32].copy_from_slice(&validation_bytes(0x00));
+        buf[32..44].copy_from_slice(b"\x88\x00\x00\x00\x00\x00\x04\x00\x20\x00\x00\x00");
+        buf[64..68].copy_from_slice(b"\x91\xEF\x01\x00");
+        buf[96..108].copy_from_slice(b"\x88\x00\x00\x00\x00\x00\x40\x0b\x21\x00\x00\x00");
+        buf
+    }
+
+    /// Build a validation entry with a valid checksum.
+    fn validation_bytes(platform: u8) -> Vec<u8> {
+        let mut entry = vec![0; 32];
+        entry[0] = 0x01;
+        entry[1] = platform;
+        entry[4..8].copy_from_slice(b"TEST");
+        entry[30..32].copy_from_slice(b"\x55\xAA");
+        let sum = entry
+            .chunks(2)
+            .fold(0u16, |acc, w| acc.wrapping_add(w[0] as u16 | (w[1] as u16) << 8));
+        let check = 0u16.wrapping_sub(sum);
+        entry[28] = check as u8;
+        entry[29] = (check >> 8) as u8;
+        entry
+    }
+
+    #[test]
+    fn test_checksum() {
+        assert!(super::checksum(&validation_bytes(0xEF)));
+        let mut entry = validation_bytes(0x00);
+        entry[4] = b'B';
+        assert!(!super::checksum(&entry));
+    }
+
+    #[test]
+    fn test_catalog() {
+        let catalog = BootCatalog::parse(&catalog_bytes()).unwrap();
+        assert_eq!(catalog.validation.platform, Platform::X86);
+        assert_eq!(catalog.validation.id, "TEST");
+        assert!(catalog.default.bootable);
+        assert_eq!(catalog.default.emulation, Emulation::NoEmulation);
+        assert_eq!(catalog.default.sector_count, 4);
+        assert_eq!(catalog.default.load_rba, 0x20);
+
+        assert_eq!(catalog.sections.len(), 1);
+        assert_eq!(catalog.sections[0].platform, Platform::Efi);
+        assert_eq!(catalog.sections[0].entries[0].sector_count, 0xb40);
+        assert_eq!(catalog.sections[0].entries[0].load_rba, 0x21);
+
+        let entries = catalog.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].0, Platform::Efi);
+    }
+
+    #[test]
+    fn test_incomplete() {
+        assert!(BootCatalog::parse(&catalog_bytes()[..80]).is_err());
+    }
+
+    #[test]
+    fn test_section_at_sector_end() {
+        // a non-final section filling the first sector
+        let mut buf = catalog_bytes();
+        buf[64..68].copy_from_slice(b"\x90\x00\x3d\x00");
+        for entry in buf[96..].chunks_mut(32) {
+            entry[..12].copy_from_slice(b"\x88\x00\x00\x00\x00\x00\x04\x00\x20\x00\x00\x00");
+        }
+        match super::catalog(&buf) {
+            Err(::nom::Err::Incomplete(_)) => (),
+            other => panic!("expected an incomplete catalog, got {:?}", other.map(|(_, c)| c)),
+        }
+
+        // the final section is read from the next sector
+        buf.extend_from_slice(&catalog_bytes()[64..]);
+        let catalog = BootCatalog::parse(&buf).unwrap();
+        assert_eq!(catalog.sections.len(), 2);
+        assert_eq!(catalog.sections[0].entries.len(), 61);
+        assert_eq!(catalog.sections[1].platform, Platform::Efi);
+    }
+}
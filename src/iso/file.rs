@@ -1,63 +1,245 @@
 use std::cmp::min;
+use std::io::Read;
+use std::io::Seek;
 use std::io::SeekFrom;
 use std::io::Result;
 
-/// Readable file located on an ISO-9660 filesystem.
-pub struct IsoFile<'a, H: 'a>
+use utils::io::seek_position;
+
+use super::record::Record;
+use super::zisofs::ZisofsReader;
+
+/// A run of bytes of a file, either contiguous or interleaved.
+///
+/// Interleaved extents are made of units of `unit` bytes separated by gaps
+/// of `gap` bytes, the gaps storing the data of other files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(in iso) struct Extent {
+    start: u64,
+    length: u64,
+    unit: u64,
+    gap: u64,
+}
+
+impl Extent {
+    /// Create an extent of `length` contiguous bytes at offset `start`.
+    pub(in iso) fn contiguous(start: u64, length: u64) -> Self {
+        Self::interleaved(start, length, 0, 0)
+    }
+
+    /// Create an extent of `length` bytes at offset `start`, interleaved
+    /// with the given unit and gap sizes (a unit size of 0 meaning the
+    /// extent is contiguous).
+    pub(in iso) fn interleaved(start: u64, length: u64, unit: u64, gap: u64) -> Self {
+        Self {
+            start,
+            length,
+            unit,
+            gap,
+        }
+    }
+
+    /// Get the physical offset of the logical `offset` of the extent, and
+    /// the number of bytes that can be read contiguously from there.
+    fn locate(&self, offset: u64) -> (u64, u64) {
+        let remaining = self.length - offset;
+        match offset.checked_div(self.unit) {
+            None => (self.start + offset, remaining),
+            Some(index) => {
+                let within = offset % self.unit;
+                let physical = self.start + index * (self.unit + self.gap) + within;
+                (physical, min(self.unit - within, remaining))
+            }
+        }
+    }
+}
+
+/// The raw contents of a file, as recorded in one or more extents.
+pub(in iso) struct RawFile<'a, H: 'a>
 where
-    H: ::std::io::Seek + ::std::io::Read,
+    H: Seek + Read,
 {
     handle: &'a mut H,
-    start: u32,
-    length: u32,
+    extents: Vec<Extent>,
+    length: u64,
     pos: u64,
 }
 
-impl<'a, H: 'a> IsoFile<'a, H>
+impl<'a, H: 'a> RawFile<'a, H>
 where
-    H: ::std::io::Seek + ::std::io::Read,
+    H: Seek + Read,
 {
-    pub fn new(handle: &'a mut H, start: u32, length: u32) -> Result<Self> {
-        handle.seek(SeekFrom::Start(start as u64))?;
-        Ok(Self {
+    /// Create a file made of the given extents, in order.
+    fn new(handle: &'a mut H, extents: Vec<Extent>) -> Self {
+        Self {
             handle,
-            start,
-            length,
+            length: extents.iter().map(|extent| extent.length).sum(),
+            extents,
             pos: 0,
-        })
+        }
     }
 }
 
-impl<'a, H: 'a> ::std::io::Read for IsoFile<'a, H>
+impl<'a, H: 'a> Read for RawFile<'a, H>
 where
-    H: ::std::io::Seek + ::std::io::Read,
+    H: Seek + Read,
 {
     fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
-        let size = min(self.length as usize - self.pos as usize, buffer.len());
-        let bytes_read = self.handle.read(&mut buffer[..size])?;
-        self.pos += bytes_read as u64;
-        Ok(bytes_read)
+        // find the extent containing the current position, and only read
+        // up to its end or to the end of the current interleaved unit
+        let mut base = 0;
+        for extent in self.extents.iter() {
+            if self.pos < base + extent.length {
+                let (start, available) = extent.locate(self.pos - base);
+                let size = min(available, buffer.len() as u64) as usize;
+                self.handle.seek(SeekFrom::Start(start))?;
+                let bytes_read = self.handle.read(&mut buffer[..size])?;
+                self.pos += bytes_read as u64;
+                return Ok(bytes_read);
+            }
+            base += extent.length;
+        }
+        Ok(0)
     }
 }
 
-impl<'a, H: 'a> ::std::io::Seek for IsoFile<'a, H>
+impl<'a, H: 'a> Seek for RawFile<'a, H>
 where
-    H: ::std::io::Seek + ::std::io::Read,
+    H: Seek + Read,
 {
     fn seek(&mut self, whence: SeekFrom) -> Result<u64> {
-        use std::io::Error;
-        use std::io::ErrorKind::InvalidInput;
+        self.pos = seek_position(self.pos, self.length, whence)?;
+        Ok(self.pos)
+    }
+}
 
-        let err = Error::new(InvalidInput, "invalid seek to a negative position");
+/// The reader used to access the contents of an `IsoFile`.
+enum Contents<'a, H: 'a>
+where
+    H: Seek + Read,
+{
+    Raw(RawFile<'a, H>),
+    Zisofs(ZisofsReader<RawFile<'a, H>>),
+}
 
-        self.pos = match whence {
-            SeekFrom::Current(x) if self.pos as i64 + x < 0 => return Err(err),
-            SeekFrom::Current(x) => min(self.pos + x as u64, self.length as u64),
-            SeekFrom::End(x) if self.length as i64 + x < 0 => return Err(err),
-            SeekFrom::End(x) => min(self.length as i64 + x, self.length as i64) as u64,
-            SeekFrom::Start(x) => min(x, self.length as u64),
+/// Readable file located on an ISO-9660 filesystem.
+///
+/// Files compressed with [zisofs] are decompressed transparently.
+///
+/// [zisofs]: https://dev.lovelyhq.com/libburnia/web/wiki/zisofs
+pub struct IsoFile<'a, H: 'a>
+where
+    H: Seek + Read,
+{
+    contents: Contents<'a, H>,
+}
+
+impl<'a, H: 'a> IsoFile<'a, H>
+where
+    H: Seek + Read,
+{
+    /// Open the file described by `record`, on a volume with the given
+    /// logical block size.
+    ///
+    /// The sections of multi-extent files are read one after the other,
+    /// following their interleaving pattern if any, and skipping the blocks
+    /// of their Extended Attribute Record.
+    pub(in iso) fn new(handle: &'a mut H, record: &Record, block_size: u16) -> Result<Self> {
+        let block_size = block_size as u64;
+        let extents = record
+            .sections()
+            .iter()
+            .map(|s| {
+                Extent::interleaved(
+                    (s.extent as u64 + s.ear_length as u64) * block_size,
+                    s.data_length as u64,
+                    s.unit_size as u64 * block_size,
+                    s.gap_size as u64 * block_size,
+                )
+            })
+            .collect();
+        let raw = RawFile::new(handle, extents);
+        let zisofs = record
+            .rock_ridge
+            .as_ref()
+            .and_then(|rr| rr.zisofs.as_ref())
+            .is_some_and(|zf| zf.is_supported());
+        let contents = if zisofs {
+            Contents::Zisofs(ZisofsReader::new(raw)?)
+        } else {
+            Contents::Raw(raw)
         };
+        Ok(Self { contents })
+    }
 
-        Ok(self.pos)
+    /// Open `length` raw bytes starting at offset `start` of `handle`.
+    pub(in iso) fn from_extent(handle: &'a mut H, start: u64, length: u64) -> Self {
+        Self {
+            contents: Contents::Raw(RawFile::new(handle, vec![Extent::contiguous(start, length)])),
+        }
+    }
+}
+
+impl<'a, H: 'a> Read for IsoFile<'a, H>
+where
+    H: Seek + Read,
+{
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        match self.contents {
+            Contents::Raw(ref mut raw) => raw.read(buffer),
+            Contents::Zisofs(ref mut zf) => zf.read(buffer),
+        }
+    }
+}
+
+impl<'a, H: 'a> Seek for IsoFile<'a, H>
+where
+    H: Seek + Read,
+{
+    fn seek(&mut self, whence: SeekFrom) -> Result<u64> {
+        match self.contents {
+            Contents::Raw(ref mut raw) => raw.seek(whence),
+            Contents::Zisofs(ref mut zf) => zf.seek(whence),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::io::Cursor;
+    use std::io::Read;
+    use std::io::Seek;
+    use std::io::SeekFrom;
+
+    use super::Extent;
+    use super::RawFile;
+
+    #[test]
+    fn test_interleaved() {
+        // units of 2 bytes separated by gaps of 3 bytes
+        let mut handle = Cursor::new(b"xxAB...CD...EF...G".to_vec());
+        let extent = Extent::interleaved(2, 7, 2, 3);
+        let mut raw = RawFile::new(&mut handle, vec![extent]);
+
+        let mut content = String::new();
+        raw.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "ABCDEFG");
+
+        raw.seek(SeekFrom::Start(3)).unwrap();
+        let mut buffer = [0; 3];
+        raw.read_exact(&mut buffer).unwrap();
+        assert_eq!(&buffer, b"DEF");
+    }
+
+    #[test]
+    fn test_extents() {
+        let mut handle = Cursor::new(b"..AB..CDE".to_vec());
+        let extents = vec![Extent::contiguous(6, 3), Extent::contiguous(2, 2)];
+        let mut content = String::new();
+        RawFile::new(&mut handle, extents)
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "CDEAB");
     }
 }
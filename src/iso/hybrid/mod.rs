@@ -0,0 +1,463 @@
+//! Hybrid system area partition tables (MBR, GPT and APM).
+//!
+//! The first 16 sectors of an ISO-9660 volume are not used by the filesystem,
+//! which allows *isohybrid* images to be booted from USB drives by recording
+//! partition tables there, the partitions of which usually overlap with files
+//! of the ISO-9660 hierarchy.
+
+mod parser;
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+use super::super::error::Result;
+
+/// The size of the system area.
+const SYSTEM_AREA_SIZE: usize = 16 * 2048;
+
+/// The size of the sectors addressed by an MBR.
+const MBR_SECTOR_SIZE: u64 = 512;
+
+/// The maximum size of a GPT partition array that will be read.
+const MAX_GPT_ARRAY_SIZE: u64 = 1 << 20;
+
+/// The maximum number of Apple Partition Map entries that will be read.
+const MAX_APM_ENTRIES: u32 = 64;
+
+/// A globally unique identifier, as used by the GPT.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Guid(pub [u8; 16]);
+
+impl Guid {
+    /// Check whether the GUID is null, i.e. only made of zeroes.
+    pub fn is_nil(&self) -> bool {
+        self.0.iter().all(|&b| b == 0)
+    }
+}
+
+impl Display for Guid {
+    /// Format the GUID in its canonical form, the first three fields of
+    /// which are stored in little-endian order.
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let b = &self.0;
+        write!(
+            f,
+            "{:02X}{:02X}{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-",
+            b[3], b[2], b[1], b[0], b[5], b[4], b[7], b[6], b[8], b[9]
+        )?;
+        b[10..].iter().try_for_each(|x| write!(f, "{:02X}", x))
+    }
+}
+
+/// A primary partition entry of a Master Boot Record.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MbrPartition {
+    /// The index of the entry in the partition table, starting at 1.
+    pub index: u8,
+    pub bootable: bool,
+    pub partition_type: u8,
+    pub start_lba: u32,
+    pub sector_count: u32,
+}
+
+/// A Master Boot Record.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mbr {
+    pub disk_signature: u32,
+    /// The non-empty partition entries.
+    pub partitions: Vec<MbrPartition>,
+}
+
+impl Mbr {
+    /// Parse a Master Boot Record from the first 512 bytes of `bytes`.
+    ///
+    /// Returns `None` if `bytes` does not end with the MBR signature.
+    pub(in iso) fn parse(bytes: &[u8]) -> Option<Self> {
+        parser::mbr(bytes).ok().map(|(_, mbr)| mbr)
+    }
+
+    /// Check whether the MBR is a protective MBR guarding a GPT.
+    pub fn is_protective(&self) -> bool {
+        self.partitions.iter().any(|p| p.partition_type == 0xEE)
+    }
+}
+
+/// The header of a GUID Partition Table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GptHeader {
+    pub revision: u32,
+    pub header_size: u32,
+    pub header_crc: u32,
+    pub current_lba: u64,
+    pub backup_lba: u64,
+    pub first_usable_lba: u64,
+    pub last_usable_lba: u64,
+    pub disk_guid: Guid,
+    pub entries_lba: u64,
+    pub entry_count: u32,
+    pub entry_size: u32,
+    pub entries_crc: u32,
+}
+
+/// A partition entry of a GUID Partition Table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GptPartition {
+    /// The index of the entry in the partition array, starting at 1.
+    pub index: u32,
+    pub type_guid: Guid,
+    pub guid: Guid,
+    pub first_lba: u64,
+    pub last_lba: u64,
+    pub attributes: u64,
+    pub name: String,
+}
+
+/// A GUID Partition Table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Gpt {
+    /// The size of the logical blocks addressed by the table.
+    pub block_size: u64,
+    pub header: GptHeader,
+    /// The used partition entries.
+    pub partitions: Vec<GptPartition>,
+    /// Whether the CRC32 of the header is valid.
+    pub header_valid: bool,
+    /// Whether the CRC32 of the partition array is valid.
+    pub entries_valid: bool,
+}
+
+impl Gpt {
+    /// Check whether both the header and the partition array are valid.
+    pub fn is_valid(&self) -> bool {
+        self.header_valid && self.entries_valid
+    }
+}
+
+/// A partition entry of an Apple Partition Map.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApmPartition {
+    /// The index of the entry in the partition map, starting at 1.
+    pub index: u32,
+    pub start_block: u32,
+    pub block_count: u32,
+    pub name: String,
+    pub partition_type: String,
+}
+
+/// An Apple Partition Map.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Apm {
+    /// The size of the blocks addressed by the map.
+    pub block_size: u16,
+    pub partitions: Vec<ApmPartition>,
+}
+
+/// The partitioning scheme a partition was declared in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scheme {
+    Mbr,
+    Gpt,
+    Apm,
+}
+
+/// A partition of any scheme, located in bytes from the start of the volume.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Partition {
+    pub scheme: Scheme,
+    /// The index of the partition in its table, starting at 1.
+    pub index: u32,
+    pub offset: u64,
+    pub length: u64,
+}
+
+impl Partition {
+    /// Check whether the partition overlaps with `length` bytes at `offset`.
+    ///
+    /// Ranges ending past `u64::MAX` are considered unbounded.
+    pub fn overlaps(&self, offset: u64, length: u64) -> bool {
+        offset < self.offset.saturating_add(self.length) && self.offset < offset.saturating_add(length)
+    }
+}
+
+/// The partition tables recorded in the system area of a hybrid volume.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SystemArea {
+    pub mbr: Option<Mbr>,
+    pub gpt: Option<Gpt>,
+    pub apm: Option<Apm>,
+}
+
+impl SystemArea {
+    /// Read the partition tables of the system area of a volume.
+    ///
+    /// Tables that cannot be found or parsed are left empty.
+    pub(in iso) fn read<H: Read + Seek>(handle: &mut H) -> Result<Self> {
+        let mut buffer = vec![0; SYSTEM_AREA_SIZE];
+        handle.seek(SeekFrom::Start(0))?;
+        handle.read_exact(&mut buffer)?;
+
+        Ok(Self {
+            mbr: Mbr::parse(&buffer),
+            gpt: Self::read_gpt(&buffer, handle)?,
+            apm: Self::read_apm(&buffer, handle)?,
+        })
+    }
+
+    fn read_gpt<H: Read + Seek>(buffer: &[u8], handle: &mut H) -> Result<Option<Gpt>> {
+        // the GPT header is at LBA 1, with 512-byte or 2048-byte blocks
+        for &block_size in [512, 2048].iter() {
+            let raw = &buffer[block_size as usize..];
+            let header = match parser::gpt_header(raw) {
+                Ok((_, header)) => header,
+                Err(_) => continue,
+            };
+
+            let header_valid = parser::header_crc(raw, header.header_size) == Some(header.header_crc);
+
+            let array_size = header.entry_count as u64 * header.entry_size as u64;
+            let array_offset = match header.entries_lba.checked_mul(block_size) {
+                Some(offset) if header.entry_size >= 128 && array_size <= MAX_GPT_ARRAY_SIZE => offset,
+                _ => {
+                    return Ok(Some(Gpt {
+                        block_size,
+                        header,
+                        partitions: Vec::new(),
+                        header_valid,
+                        entries_valid: false,
+                    }))
+                }
+            };
+
+            let mut array = vec![0; array_size as usize];
+            handle.seek(SeekFrom::Start(array_offset))?;
+            handle.read_exact(&mut array)?;
+
+            let partitions = array
+                .chunks(header.entry_size as usize)
+                .enumerate()
+                .filter_map(|(i, raw)| parser::gpt_partition(raw, i as u32 + 1).ok())
+                .map(|(_, partition)| partition)
+                .filter(|partition| !partition.type_guid.is_nil())
+                .collect();
+
+            return Ok(Some(Gpt {
+                block_size,
+                entries_valid: parser::crc32(&array) == header.entries_crc,
+                header,
+                partitions,
+                header_valid,
+            }));
+        }
+
+        Ok(None)
+    }
+
+    fn read_apm<H: Read + Seek>(buffer: &[u8], handle: &mut H) -> Result<Option<Apm>> {
+        let block_size = match parser::driver_descriptor(buffer) {
+            Ok((_, block_size)) => block_size,
+            Err(_) => return Ok(None),
+        };
+
+        let mut partitions: Vec<ApmPartition> = Vec::new();
+        let mut raw = vec![0; block_size as usize];
+        let mut count = 1;
+        let mut index = 1;
+
+        // the number of entries is recorded in every entry of the map
+        while index <= count && index <= MAX_APM_ENTRIES {
+            handle.seek(SeekFrom::Start(index as u64 * block_size as u64))?;
+            handle.read_exact(&mut raw)?;
+            match parser::apm_partition(&raw, index) {
+                Ok((_, (map_entries, partition))) => {
+                    count = map_entries;
+                    partitions.push(partition);
+                }
+                Err(_) => break,
+            }
+            index += 1;
+        }
+
+        Ok(Some(Apm {
+            block_size,
+            partitions,
+        }))
+    }
+
+    /// Get the partitions of all the tables, located in bytes.
+    ///
+    /// GPT partitions whose end in bytes does not fit in a `u64` are skipped.
+    pub fn partitions(&self) -> Vec<Partition> {
+        let mut partitions = Vec::new();
+
+        if let Some(ref mbr) = self.mbr {
+            partitions.extend(mbr.partitions.iter().map(|p| Partition {
+                scheme: Scheme::Mbr,
+                index: p.index as u32,
+                offset: p.start_lba as u64 * MBR_SECTOR_SIZE,
+                length: p.sector_count as u64 * MBR_SECTOR_SIZE,
+            }));
+        }
+
+        if let Some(ref gpt) = self.gpt {
+            partitions.extend(gpt.partitions.iter().filter_map(|p| {
+                let blocks = p.last_lba.checked_add(1)?.saturating_sub(p.first_lba);
+                let offset = p.first_lba.checked_mul(gpt.block_size)?;
+                let length = blocks.checked_mul(gpt.block_size)?;
+                offset.checked_add(length)?;
+                Some(Partition {
+                    scheme: Scheme::Gpt,
+                    index: p.index,
+                    offset,
+                    length,
+                })
+            }));
+        }
+
+        if let Some(ref apm) = self.apm {
+            let block_size = apm.block_size as u64;
+            partitions.extend(apm.partitions.iter().map(|p| Partition {
+                scheme: Scheme::Apm,
+                index: p.index,
+                offset: p.start_block as u64 * block_size,
+                length: p.block_count as u64 * block_size,
+            }));
+        }
+
+        partitions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::io::Cursor;
+
+    use super::*;
+
+    /// Build a system area with a protective MBR, a GPT with a single EFI
+    /// System Partition, and an Apple Partition Map with two entries.
+    fn system_area() -> Vec<u8> {
+        let mut img = vec![0; SYSTEM_AREA_SIZE + 2048];
+
+        // DDM with 2048-byte blocks
+        img[..4].copy_from_slice(b"ER\x08\x00");
+
+        // protective MBR
+        img[446 + 4] = 0xEE;
+        img[446 + 8] = 1;
+        img[446 + 12..446 + 16].copy_from_slice(&100u32.to_le_bytes());
+        img[510..512].copy_from_slice(b"\x55\xAA");
+
+        // GPT partition array at LBA 2, one used entry
+        let esp = 1024;
+        img[esp..esp + 16].copy_from_slice(&[
+            0x28, 0x73, 0x2A, 0xC1, 0x1F, 0xF8, 0xD2, 0x11,
+            0xBA, 0x4B, 0x00, 0xA0, 0xC9, 0x3E, 0xC9, 0x3B,
+        ]);
+        img[esp + 16] = 1;
+        img[esp + 32..esp + 40].copy_from_slice(&64u64.to_le_bytes());
+        img[esp + 40..esp + 48].copy_from_slice(&71u64.to_le_bytes());
+        for (i, c) in "EFI".encode_utf16().enumerate() {
+            img[esp + 56 + 2 * i..esp + 58 + 2 * i].copy_from_slice(&c.to_le_bytes());
+        }
+        let entries_crc = parser::crc32(&img[1024..1024 + 4 * 128]);
+
+        // GPT header at LBA 1, then its CRC32
+        let h = 512;
+        img[h..h + 8].copy_from_slice(b"EFI PART");
+        img[h + 8..h + 12].copy_from_slice(&0x0001_0000u32.to_le_bytes());
+        img[h + 12..h + 16].copy_from_slice(&92u32.to_le_bytes());
+        img[h + 24..h + 32].copy_from_slice(&1u64.to_le_bytes());
+        img[h + 72..h + 80].copy_from_slice(&2u64.to_le_bytes());
+        img[h + 80..h + 84].copy_from_slice(&4u32.to_le_bytes());
+        img[h + 84..h + 88].copy_from_slice(&128u32.to_le_bytes());
+        img[h + 88..h + 92].copy_from_slice(&entries_crc.to_le_bytes());
+        let header_crc = parser::crc32(&img[h..h + 92]);
+        img[h + 16..h + 20].copy_from_slice(&header_crc.to_le_bytes());
+
+        // APM entries at blocks 1 and 2
+        for (i, name) in [&b"Apple"[..], &b"EFI"[..]].iter().enumerate() {
+            let e = 2048 * (i + 1);
+            img[e..e + 2].copy_from_slice(b"PM");
+            img[e + 4..e + 8].copy_from_slice(&2u32.to_be_bytes());
+            img[e + 8..e + 12].copy_from_slice(&(i as u32 * 16 + 1).to_be_bytes());
+            img[e + 12..e + 16].copy_from_slice(&2u32.to_be_bytes());
+            img[e + 16..e + 16 + name.len()].copy_from_slice(name);
+        }
+
+        img
+    }
+
+    #[test]
+    fn test_read() {
+        let area = SystemArea::read(&mut Cursor::new(system_area())).unwrap();
+
+        let mbr = area.mbr.as_ref().unwrap();
+        assert!(mbr.is_protective());
+        assert_eq!(mbr.partitions.len(), 1);
+
+        let gpt = area.gpt.as_ref().unwrap();
+        assert!(gpt.is_valid());
+        assert_eq!(gpt.partitions.len(), 1);
+        assert_eq!(gpt.partitions[0].name, "EFI");
+        assert_eq!(
+            gpt.partitions[0].type_guid.to_string(),
+            "C12A7328-F81F-11D2-BA4B-00A0C93EC93B"
+        );
+
+        let apm = area.apm.as_ref().unwrap();
+        assert_eq!(apm.block_size, 2048);
+        assert_eq!(apm.partitions.len(), 2);
+        assert_eq!(apm.partitions[1].name, "EFI");
+
+        let partitions = area.partitions();
+        assert_eq!(partitions.len(), 4);
+        assert_eq!(partitions[1].offset, 64 * 512);
+        assert_eq!(partitions[1].length, 8 * 512);
+        assert_eq!(partitions[3].offset, 17 * 2048);
+        assert!(partitions[3].overlaps(17 * 2048 + 10, 1));
+        assert!(!partitions[3].overlaps(19 * 2048, 2048));
+    }
+
+    #[test]
+    fn test_read_corrupted_gpt() {
+        let mut img = system_area();
+        img[1024 + 56] = b'X';
+        let area = SystemArea::read(&mut Cursor::new(img)).unwrap();
+        let gpt = area.gpt.unwrap();
+        assert!(gpt.header_valid);
+        assert!(!gpt.entries_valid);
+    }
+
+    #[test]
+    fn test_partitions_overflow() {
+        let mut area = SystemArea::read(&mut Cursor::new(system_area())).unwrap();
+        if let Some(ref mut gpt) = area.gpt {
+            gpt.partitions[0].first_lba = u64::MAX / 2;
+            gpt.partitions[0].last_lba = u64::MAX;
+        }
+        let partitions = area.partitions();
+        assert_eq!(partitions.len(), 3);
+        assert!(partitions.iter().all(|p| p.scheme != Scheme::Gpt));
+
+        let partition = Partition {
+            scheme: Scheme::Gpt,
+            index: 1,
+            offset: u64::MAX - 1,
+            length: u64::MAX,
+        };
+        assert!(partition.overlaps(u64::MAX - 1, u64::MAX));
+        assert!(!partition.overlaps(0, 2));
+    }
+
+    #[test]
+    fn test_read_empty() {
+        let area = SystemArea::read(&mut Cursor::new(vec![0; SYSTEM_AREA_SIZE])).unwrap();
+        assert_eq!(area, SystemArea::default());
+        assert!(area.partitions().is_empty());
+    }
+}
@@ -0,0 +1,211 @@
+use crc32fast::Hasher;
+use nom::be_u16;
+use nom::be_u32;
+use nom::be_u8;
+use nom::le_u32;
+use nom::le_u64;
+
+use utils::charsets::padded;
+use utils::charsets::utf16_le;
+
+use super::ApmPartition;
+use super::GptHeader;
+use super::GptPartition;
+use super::Guid;
+use super::Mbr;
+use super::MbrPartition;
+
+/// Compute the CRC32 checksum of `input`.
+pub fn crc32(input: &[u8]) -> u32 {
+    let mut hasher = Hasher::new();
+    hasher.update(input);
+    hasher.finalize()
+}
+
+/// Compute the CRC32 checksum of a GPT header of `size` bytes, with its
+/// own checksum field zeroed.
+pub fn header_crc(input: &[u8], size: u32) -> Option<u32> {
+    let size = size as usize;
+    if size < 92 || size > input.len() {
+        return None;
+    }
+    let mut hasher = Hasher::new();
+    hasher.update(&input[..16]);
+    hasher.update(&[0; 4]);
+    hasher.update(&input[20..size]);
+    Some(hasher.finalize())
+}
+
+#[rustfmt::skip]
+named!(guid(&[u8]) -> Guid,
+    map!(take!(16), |bytes| {
+        let mut guid = [0; 16];
+        guid.copy_from_slice(bytes);
+        Guid(guid)
+    })
+);
+
+#[rustfmt::skip]
+named_args!(mbr_partition(index: u8)<MbrPartition>,
+    do_parse!(
+        status:         be_u8                                >>
+                        take!(3)                             >>
+        partition_type: be_u8                                >>
+                        take!(3)                             >>
+        start_lba:      le_u32                               >>
+        sector_count:   le_u32                               >>
+                        (MbrPartition {
+                            index,
+                            bootable: status & 0x80 != 0,
+                            partition_type,
+                            start_lba,
+                            sector_count,
+                        })
+    )
+);
+
+#[rustfmt::skip]
+named!(pub mbr(&[u8]) -> Mbr,
+    do_parse!(
+                        take!(440)                           >>
+        disk_signature: le_u32                               >>
+                        take!(2)                             >>
+        p1:             apply!(mbr_partition, 1)             >>
+        p2:             apply!(mbr_partition, 2)             >>
+        p3:             apply!(mbr_partition, 3)             >>
+        p4:             apply!(mbr_partition, 4)             >>
+                        tag!(b"\x55\xAA")                    >>
+                        (Mbr {
+                            disk_signature,
+                            partitions: vec![p1, p2, p3, p4]
+                                .into_iter()
+                                .filter(|p| p.partition_type != 0 && p.sector_count != 0)
+                                .collect(),
+                        })
+    )
+);
+
+#[rustfmt::skip]
+named!(pub gpt_header(&[u8]) -> GptHeader,
+    do_parse!(
+                          tag!(b"EFI PART")                  >>
+        revision:         le_u32                             >>
+        header_size:      le_u32                             >>
+        header_crc:       le_u32                             >>
+                          take!(4)                           >>
+        current_lba:      le_u64                             >>
+        backup_lba:       le_u64                             >>
+        first_usable_lba: le_u64                             >>
+        last_usable_lba:  le_u64                             >>
+        disk_guid:        guid                               >>
+        entries_lba:      le_u64                             >>
+        entry_count:      le_u32                             >>
+        entry_size:       le_u32                             >>
+        entries_crc:      le_u32                             >>
+                          (GptHeader {
+                              revision,
+                              header_size,
+                              header_crc,
+                              current_lba,
+                              backup_lba,
+                              first_usable_lba,
+                              last_usable_lba,
+                              disk_guid,
+                              entries_lba,
+                              entry_count,
+                              entry_size,
+                              entries_crc,
+                          })
+    )
+);
+
+#[rustfmt::skip]
+named_args!(pub gpt_partition(index: u32)<GptPartition>,
+    do_parse!(
+        type_guid:  guid                                     >>
+        guid:       guid                                     >>
+        first_lba:  le_u64                                   >>
+        last_lba:   le_u64                                   >>
+        attributes: le_u64                                   >>
+        name:       take!(72)                                >>
+                    (GptPartition {
+                        index,
+                        type_guid,
+                        guid,
+                        first_lba,
+                        last_lba,
+                        attributes,
+                        name: utf16_le(name),
+                    })
+    )
+);
+
+#[rustfmt::skip]
+named!(pub driver_descriptor(&[u8]) -> u16,
+    do_parse!(
+                    tag!(b"ER")                              >>
+        block_size: verify!(be_u16, |x: u16| x.is_power_of_two() && x >= 512) >>
+                    (block_size)
+    )
+);
+
+#[rustfmt::skip]
+named_args!(pub apm_partition(index: u32)<(u32, ApmPartition)>,
+    do_parse!(
+                        tag!(b"PM")                          >>
+                        take!(2)                             >>
+        map_entries:    be_u32                               >>
+        start_block:    be_u32                               >>
+        block_count:    be_u32                               >>
+        name:           take!(32)                            >>
+        partition_type: take!(32)                            >>
+                        ((map_entries, ApmPartition {
+                            index,
+                            start_block,
+                            block_count,
+                            name: padded(name),
+                            partition_type: padded(partition_type),
+                        }))
+    )
+);
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(super::crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_mbr() {
+        let mut raw = vec![0; 512];
+        raw[440..444].copy_from_slice(b"\x78\x56\x34\x12");
+        raw[446..462].copy_from_slice(b"\x80\x00\x01\x00\x17\xFE\xFF\xFF\x00\x00\x00\x00\x00\x60\x00\x00");
+        raw[462..478].copy_from_slice(b"\x00\xFE\xFF\xFF\xEF\xFE\xFF\xFF\x10\x01\x00\x00\x40\x00\x00\x00");
+        raw[510..512].copy_from_slice(b"\x55\xAA");
+
+        let (_, mbr) = super::mbr(&raw).unwrap();
+        assert_eq!(mbr.disk_signature, 0x1234_5678);
+        assert_eq!(mbr.partitions.len(), 2);
+        assert!(mbr.partitions[0].bootable);
+        assert_eq!(mbr.partitions[0].partition_type, 0x17);
+        assert_eq!(mbr.partitions[0].sector_count, 0x6000);
+        assert_eq!(mbr.partitions[1].index, 2);
+        assert_eq!(mbr.partitions[1].partition_type, 0xEF);
+        assert_eq!(mbr.partitions[1].start_lba, 0x110);
+        assert!(!mbr.is_protective());
+    }
+
+    #[test]
+    fn test_mbr_signature() {
+        assert!(super::mbr(&[0; 512]).is_err());
+    }
+
+    #[test]
+    fn test_driver_descriptor() {
+        assert_eq!(super::driver_descriptor(b"ER\x08\x00").unwrap().1, 2048);
+        assert!(super::driver_descriptor(b"ER\x00\x03").is_err());
+        assert!(super::driver_descriptor(b"\x33\xed\x90\x90").is_err());
+    }
+}
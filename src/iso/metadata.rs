@@ -1,15 +1,25 @@
 use std::convert::From;
+use std::ffi::OsStr;
 use std::io::Read;
 use std::io::Seek;
+use std::io::SeekFrom;
 use std::path::Path;
 use std::rc::Rc;
 
+use chrono::DateTime;
+use chrono::offset::FixedOffset;
+
 use super::super::error::Error;
 use super::super::error::ErrorKind;
 use super::super::error::Result;
 
+use super::ear::ExtendedAttributes;
 use super::file::IsoFile;
 use super::node::Node;
+use super::rockridge::PosixAttributes;
+use super::rockridge::RockRidge;
+use super::susp;
+use super::susp::Entry;
 use super::IsoFs;
 
 
@@ -31,9 +41,122 @@ impl Metadata {
         !self.is_dir()
     }
 
+    /// Returns whether this resource is hidden, i.e. has the *existence*
+    /// flag set.
+    pub fn is_hidden(&self) -> bool {
+        self.0.as_ref().record.is_hidden
+    }
+
+    /// Returns whether this metadata is for an associated file.
+    pub fn is_associated(&self) -> bool {
+        self.0.as_ref().record.is_associated
+    }
+
+    /// Returns whether the structure of this file is described by the record
+    /// format of its Extended Attribute Record.
+    pub fn has_record_format(&self) -> bool {
+        self.0.as_ref().record.has_record_format
+    }
+
+    /// Returns whether owner and permissions of this resource are recorded
+    /// in its Extended Attribute Record.
+    pub fn is_protected(&self) -> bool {
+        self.0.as_ref().record.is_protected
+    }
+
+    /// Returns whether this file is recorded in several sections.
+    pub fn is_multi_extent(&self) -> bool {
+        self.0.as_ref().record.sections().len() > 1
+    }
+
+    /// Return the associated file of this file, if it has one.
+    ///
+    /// Associated files were mostly used to store the resource forks of
+    /// Macintosh files.
+    pub fn associated_file(&self) -> Option<Metadata> {
+        self.0.as_ref().associated.clone().map(Metadata::from)
+    }
+
+    /// Returns whether this metadata is for a symbolic link.
+    ///
+    /// Symbolic links can only be recorded with the Rock Ridge extensions.
+    pub fn is_symlink(&self) -> bool {
+        self.symlink_target().is_some()
+    }
+
+    /// Return the size of the file, in bytes, this metadata is for.
+    ///
+    /// The uncompressed size is returned for files compressed with zisofs,
+    /// and the size of all sections for multi-extent files.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u64 {
+        let record = &self.0.as_ref().record;
+        match record.rock_ridge.as_ref().and_then(|rr| rr.zisofs.as_ref()) {
+            Some(zf) if zf.is_supported() => zf.size as u64,
+            _ => record.length(),
+        }
+    }
+
     /// Return the name of the resource this metadata is for.
+    ///
+    /// The Rock Ridge name is used when there is one.
     pub fn name(&self) -> &str {
-        self.0.as_ref().record.name.as_ref()
+        self.0.as_ref().name()
+    }
+
+    /// Return the file identifier of the record as stored on disc.
+    ///
+    /// Unlike [`name`](#method.name), the identifier is not decoded and
+    /// still includes the version number of the file, if any.
+    pub fn identifier(&self) -> &[u8] {
+        &self.0.as_ref().record.identifier
+    }
+
+    /// Return the name of the resource as recorded, without its version
+    /// number.
+    ///
+    /// Unlike [`name`](#method.name), identifiers of the primary and
+    /// enhanced hierarchies are not decoded, so that names recorded in an
+    /// unknown character set can be used to build a path. Rock Ridge and
+    /// Joliet names are encoded in UTF-8. On platforms other than Unix, the
+    /// decoded name is returned.
+    pub fn file_name(&self) -> &OsStr {
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            OsStr::from_bytes(self.0.as_ref().raw_name())
+        }
+        #[cfg(not(unix))]
+        {
+            OsStr::new(self.name())
+        }
+    }
+
+    /// Return the version number of the file this metadata is for, if any.
+    pub fn version(&self) -> Option<u8> {
+        self.0.as_ref().record.version
+    }
+
+    /// Return the sequence number of the volume of the volume set the
+    /// extent of this resource is recorded on.
+    pub fn volume_sequence_number(&self) -> u16 {
+        self.0.as_ref().record.seq_number
+    }
+
+    /// Return the date the resource this metadata is for was recorded, as
+    /// stored in its directory record.
+    ///
+    /// Returns `None` if the date is unset or invalid: use
+    /// [`raw_recording_date`](#method.raw_recording_date) to tell them
+    /// apart.
+    pub fn recording_date(&self) -> Option<DateTime<FixedOffset>> {
+        self.0.as_ref().record.date
+    }
+
+    /// Return the recording date of the resource as stored in its directory
+    /// record, which is made of null bytes if the date is unset.
+    pub fn raw_recording_date(&self) -> &[u8] {
+        &self.0.as_ref().record.raw_date
     }
 
     /// Return the absolute path to the resource this metadata is for.
@@ -41,6 +164,23 @@ impl Metadata {
         &self.0.as_ref().path
     }
 
+    /// Return the Rock Ridge extensions recorded for this resource, if any.
+    pub fn rock_ridge(&self) -> Option<&RockRidge> {
+        self.0.as_ref().record.rock_ridge.as_ref()
+    }
+
+    /// Return the POSIX attributes of this resource, if any.
+    pub fn posix_attributes(&self) -> Option<&PosixAttributes> {
+        self.rock_ridge().and_then(|rr| rr.posix.as_ref())
+    }
+
+    /// Return the target of this symbolic link, if this is one.
+    pub fn symlink_target(&self) -> Option<&Path> {
+        self.rock_ridge()
+            .and_then(|rr| rr.symlink.as_ref())
+            .map(|p| p.as_path())
+    }
+
     /// Given an ISO-9660 filesystem, return the contents of this directory.
     ///
     /// This method can be used to implement recursive functions using metadata
@@ -77,9 +217,11 @@ impl Metadata {
     /// this could possibly cause the internal parser to panic.
     pub fn read_dir<H: Seek + Read>(&self, iso: &mut IsoFs<H>) -> Result<Vec<Self>> {
         if self.is_dir() {
+            let hidden = iso.hidden;
             let contents = self.0
                 .children(&mut iso.handle)?
                 .into_iter()
+                .filter(|child| hidden || !child.record.is_hidden)
                 .map(Self::from)
                 .collect();
             Ok(contents)
@@ -88,11 +230,53 @@ impl Metadata {
         }
     }
 
+    /// Given an ISO-9660 filesystem, open this resource as a file.
+    ///
+    /// # Errors
+    ///
+    /// * [`VolumeNotFound`](../error/enum.ErrorKind.html#variant.VolumeNotFound)
+    ///   when the file is recorded on another volume of a volume set
     pub fn open_file<'a, H: Seek + Read + 'a>(&self, iso: &'a mut IsoFs<H>) -> Result<IsoFile<'a, H>> {
-        let start = self.0.record.extent * iso.block_size as u32;
-        IsoFile::new(&mut iso.handle, start, self.0.record.data_length).map_err(Error::from)
+        iso.check_volume(&self.0.record)?;
+        IsoFile::new(&mut iso.handle, &self.0.record, iso.block_size).map_err(Error::from)
+    }
+
+    /// Given an ISO-9660 filesystem, return the system use entries of this
+    /// resource.
+    ///
+    /// Continuation areas are followed, and entries of extensions other
+    /// than the SUSP itself are returned as
+    /// [`Entry::Unknown`](susp/enum.Entry.html#variant.Unknown).
+    pub fn system_use_entries<H: Seek + Read>(&self, iso: &mut IsoFs<H>) -> Result<Vec<Entry>> {
+        let skip = self.0.susp.unwrap_or(0);
+        susp::read(&self.0.record.system_use, skip, &mut iso.handle)
     }
 
+    /// Returns whether this resource has an Extended Attribute Record.
+    pub fn has_extended_attributes(&self) -> bool {
+        self.0.as_ref().record.ear_length > 0
+    }
+
+    /// Given an ISO-9660 filesystem, return the Extended Attribute Record
+    /// of this resource, if it has one.
+    ///
+    /// # Errors
+    ///
+    /// * [`VolumeNotFound`](../error/enum.ErrorKind.html#variant.VolumeNotFound)
+    ///   when the record is stored on another volume of a volume set
+    pub fn extended_attributes<H: Seek + Read>(&self, iso: &mut IsoFs<H>) -> Result<Option<ExtendedAttributes>> {
+        let record = &self.0.as_ref().record;
+        if record.ear_length == 0 {
+            return Ok(None);
+        }
+        iso.check_volume(record)?;
+
+        let block_size = iso.block_size as u64;
+        let mut buffer = vec![0; record.ear_length as usize * block_size as usize];
+        iso.handle.seek(SeekFrom::Start(record.extent as u64 * block_size))?;
+        iso.handle.read_exact(&mut buffer)?;
+        ExtendedAttributes::parse(&buffer).map(Some)
+    }
 }
 
 #[doc(hidden)]
@@ -48,19 +48,72 @@
 //! [`&mut self`]: https://doc.rust-lang.org/1.8.0/book/references-and-borrowing.html#mut-references
 //! [`RefCell`]: https://doc.rust-lang.org/beta/std/cell/index.html
 
+pub mod susp;
+
 mod descriptors;
+mod ear;
+mod eltorito;
 mod file;
+mod hybrid;
 mod metadata;
 mod node;
+mod options;
+mod path_table;
+mod readdir;
 mod record;
+mod rockridge;
+mod session;
+mod volume_set;
+mod zisofs;
 
 mod constants {
     pub const SECTOR_SIZE: u64 = 2048;
     pub const DEFAULT_BLOCK_SIZE: u64 = 2048;
 }
 
+pub use self::descriptors::BootRecord;
+pub use self::descriptors::Descriptors;
+pub use self::descriptors::PrimaryVolumeDescriptor;
+pub use self::descriptors::SetTerminator;
+pub use self::descriptors::SupplementaryVolumeDescriptor;
+pub use self::descriptors::VolumeDescriptor;
+pub use self::descriptors::VolumeInfo;
+pub use self::descriptors::VolumePartitionDescriptor;
+pub use self::ear::ExtendedAttributes;
+pub use self::eltorito::BootCatalog;
+pub use self::eltorito::BootEntry;
+pub use self::eltorito::Emulation;
+pub use self::eltorito::Platform;
+pub use self::eltorito::Section;
+pub use self::eltorito::ValidationEntry;
 pub use self::file::IsoFile;
+pub use self::hybrid::Apm;
+pub use self::hybrid::ApmPartition;
+pub use self::hybrid::Gpt;
+pub use self::hybrid::GptHeader;
+pub use self::hybrid::GptPartition;
+pub use self::hybrid::Guid;
+pub use self::hybrid::Mbr;
+pub use self::hybrid::MbrPartition;
+pub use self::hybrid::Partition;
+pub use self::hybrid::Scheme;
+pub use self::hybrid::SystemArea;
 pub use self::metadata::Metadata;
+pub use self::options::LookupPolicy;
+pub use self::options::Options;
+pub use self::options::Tree;
+pub use self::path_table::ByteOrder;
+pub use self::path_table::PathTable;
+pub use self::path_table::PathTableEntry;
+pub use self::path_table::PathTableLocations;
+pub use self::readdir::ReadDir;
+pub use self::rockridge::PosixAttributes;
+pub use self::rockridge::RockRidge;
+pub use self::rockridge::Timestamps;
+pub use self::rockridge::Zisofs;
+pub use self::session::Session;
+pub use self::volume_set::IsoVolumeSet;
+pub use utils::charsets::Charset;
 
 use std::io::Read;
 use std::io::Seek;
@@ -70,66 +123,150 @@ use std::rc::Rc;
 use super::error::Result;
 use super::error::Error;
 use super::error::ErrorKind;
+use super::fat::BootSector;
+use super::fat::FatFs;
+
+use utils::charsets::os_bytes;
 
 use self::node::Node;
+use self::record::Format;
+use self::record::Record;
 
 /// An ISO-9660 filesystem.
 pub struct IsoFs<H: Read + Seek> {
     handle: H,
     root: Rc<Node>,
     block_size: u16,
+    joliet_level: Option<u8>,
+    volume_info: VolumeInfo,
+    descriptors: Vec<(u32, VolumeDescriptor)>,
+    sessions: Vec<Session>,
+    session: usize,
+    boot_catalog: Option<u32>,
+    path_tables: PathTableLocations,
+    path_table_lookup: bool,
+    path_table: Option<PathTable>,
+    hidden: bool,
+    lookup: LookupPolicy,
 }
 
 // Constructor
 impl<H: Read + Seek> IsoFs<H> {
     /// Open an `IsoFs` stored in the given handle.
-    pub fn new(mut handle: H) -> Result<Self> {
-        use self::descriptors::VolumeDescriptor;
-
-        let mut block_size: Option<u16> = None;
-        let mut root: Option<Node> = None;
-
-        let mut descriptors: Vec<VolumeDescriptor> = Vec::new();
-        let mut offset = 0x10;
-        let mut buff = [0; self::constants::SECTOR_SIZE as usize];
-        let mut terminated = false;
-
-        // Go to the 16th logical sector
-        handle.seek(::std::io::SeekFrom::Start(
-            offset * self::constants::SECTOR_SIZE,
-        ))?;
-
-        // Read all volume descriptors and extract data from the PVD
-        while let (false, Ok(vd)) = (
-            terminated,
-            handle
-                .read_exact(&mut buff)
-                .map_err(Error::from)
-                .and_then(|_| VolumeDescriptor::parse(&buff)),
-        ) {
-            offset += 1;
-            if let VolumeDescriptor::Terminator(_) = vd {
-                terminated = true
-            } else if let VolumeDescriptor::Primary(ref pvd) = vd {
-                block_size = Some(pvd.block_size);
-                root = Some(Node::create_root(pvd.root.clone()))
-            }
+    pub fn new(handle: H) -> Result<Self> {
+        Self::with_options(handle, Options::default())
+    }
 
-            descriptors.push(vd);
-        }
+    /// Open an `IsoFs` stored in the given handle using the given options.
+    ///
+    /// On multisession discs, the last session is opened unless another
+    /// one was selected with [`Options::session`](struct.Options.html#method.session).
+    ///
+    /// # Errors
+    ///
+    /// * [`NoPrimaryVolumeDescriptor`](../error/enum.ErrorKind.html#variant.NoPrimaryVolumeDescriptor)
+    ///   when no session with a volume descriptor set could be found, or
+    ///   when the requested session has none
+    /// * [`SessionNotFound`](../error/enum.ErrorKind.html#variant.SessionNotFound)
+    ///   when the requested session does not exist
+    /// * [`NoJolietVolumeDescriptor`](../error/enum.ErrorKind.html#variant.NoJolietVolumeDescriptor)
+    ///   when the Joliet hierarchy was requested but none could be found
+    /// * [`NoEnhancedVolumeDescriptor`](../error/enum.ErrorKind.html#variant.NoEnhancedVolumeDescriptor)
+    ///   when the enhanced hierarchy was requested but none could be found
+    pub fn with_options(mut handle: H, options: Options) -> Result<Self> {
 
-        // Assert the loop did not break because of an error
-        if !terminated {
-            bail!(ErrorKind::NoPrimaryVolumeDescriptor);
+        let mut sessions = session::discover(&mut handle, options.toc.as_deref())?;
+        let session = match options.session {
+            Some(index) if index >= sessions.len() => bail!(ErrorKind::SessionNotFound(index)),
+            Some(index) if sessions[index].0.info.is_none() => bail!(ErrorKind::NoPrimaryVolumeDescriptor),
+            Some(index) => index,
+            None => match sessions.iter().rposition(|(session, _)| session.info.is_some()) {
+                Some(index) => index,
+                None => bail!(ErrorKind::NoPrimaryVolumeDescriptor),
+            },
+        };
+        let descriptors = ::std::mem::take(&mut sessions[session].1);
+        let sessions = sessions.into_iter().map(|(session, _)| session).collect();
+
+        let mut primary: Option<PrimaryVolumeDescriptor> = None;
+        let mut joliet: Option<(Record, u16, PathTableLocations, u8)> = None;
+        let mut enhanced: Option<(Record, u16, PathTableLocations)> = None;
+        let mut boot_catalog: Option<u32> = None;
+
+        // Extract data from the PVD and SVDs of the selected session
+        for (_, vd) in &descriptors {
+            match *vd {
+                VolumeDescriptor::Boot(ref br) if boot_catalog.is_none() => {
+                    boot_catalog = br.boot_catalog()
+                }
+                VolumeDescriptor::Primary(ref pvd) => primary = Some(pvd.as_ref().clone()),
+                VolumeDescriptor::Supplementary(ref svd) if joliet.is_none() => {
+                    if let Some(level) = svd.joliet_level() {
+                        joliet = Some((svd.root.clone(), svd.block_size, svd.path_tables, level))
+                    }
+                }
+                VolumeDescriptor::Enhanced(ref evd) if enhanced.is_none() => {
+                    enhanced = Some((evd.root.clone(), evd.block_size, evd.path_tables))
+                }
+                _ => (),
+            }
         }
 
+        let pvd = match primary {
+            Some(pvd) => pvd,
+            None => bail!(ErrorKind::NoPrimaryVolumeDescriptor),
+        };
+        let joliet_level = joliet.as_ref().map(|&(_, _, _, level)| level);
+        let (root, block_size, path_tables) = match options.tree {
+            Tree::Primary if pvd.is_high_sierra() => (
+                Node::create_root(pvd.root, Format::HighSierra(options.charset), false, None),
+                pvd.block_size,
+                pvd.path_tables,
+            ),
+            Tree::Primary => {
+                let susp = susp::detect(&pvd.root, &mut handle)?;
+                (
+                    Node::create_root(pvd.root, Format::Iso(options.charset), options.rock_ridge, susp),
+                    pvd.block_size,
+                    pvd.path_tables,
+                )
+            }
+            Tree::Joliet => match joliet {
+                Some((record, block_size, path_tables, _)) => (
+                    Node::create_root(record, Format::Joliet, false, None),
+                    block_size,
+                    path_tables,
+                ),
+                None => bail!(ErrorKind::NoJolietVolumeDescriptor),
+            },
+            Tree::Enhanced => match enhanced {
+                Some((record, block_size, path_tables)) => {
+                    let susp = susp::detect(&record, &mut handle)?;
+                    (
+                        Node::create_root(record, Format::Enhanced(options.charset), options.rock_ridge, susp),
+                        block_size,
+                        path_tables,
+                    )
+                }
+                None => bail!(ErrorKind::NoEnhancedVolumeDescriptor),
+            },
+        };
+
         Ok(Self {
             handle,
-            block_size: block_size.ok_or(ErrorKind::NoPrimaryVolumeDescriptor)?,
-            root: match root {
-                Some(node) => Rc::new(node),
-                None => bail!(ErrorKind::NoPrimaryVolumeDescriptor),
-            },
+            root: Rc::new(root),
+            block_size,
+            joliet_level,
+            volume_info: pvd.info,
+            descriptors,
+            sessions,
+            session,
+            boot_catalog,
+            path_tables,
+            path_table_lookup: options.path_table_lookup,
+            path_table: None,
+            hidden: options.hidden,
+            lookup: options.lookup,
         })
     }
 }
@@ -154,8 +291,379 @@ impl<B: AsRef<[u8]>> IsoFs<::std::io::Cursor<B>> {
 
 // Common methods
 impl<H: Read + Seek> IsoFs<H> {
+    /// Get the Joliet level of the volume, if it has a Joliet hierarchy.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let path = std::path::Path::new("static/iso/alpine.level1.iso");
+    /// let iso = opticaldisc::iso::IsoFs::from_path(path).unwrap();
+    /// assert_eq!(iso.joliet_level(), None);
+    /// ```
+    pub fn joliet_level(&self) -> Option<u8> {
+        self.joliet_level
+    }
+
+    /// Get the information recorded in the Primary Volume Descriptor.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let path = std::path::Path::new("static/iso/alpine.level1.iso");
+    /// let iso = opticaldisc::iso::IsoFs::from_path(path).unwrap();
+    /// assert_eq!(iso.volume_info().volume_id, "CDROM");
+    /// ```
+    pub fn volume_info(&self) -> &VolumeInfo {
+        &self.volume_info
+    }
+
+    /// Iterate over all the descriptors of the volume descriptor set, with
+    /// the number of the sector they were read from.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let path = std::path::Path::new("static/iso/alpine.level1.iso");
+    /// use opticaldisc::iso::VolumeDescriptor;
+    ///
+    /// let iso = opticaldisc::iso::IsoFs::from_path(path).unwrap();
+    /// let (sector, descriptor) = iso.descriptors().next().unwrap();
+    /// assert_eq!(sector, 16);
+    /// assert_eq!(descriptor.descriptor_type(), 1);
+    /// ```
+    pub fn descriptors(&self) -> Descriptors<'_> {
+        Descriptors::new(&self.descriptors)
+    }
+
+    /// Get the sessions of the disc, in order.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let path = std::path::Path::new("static/iso/alpine.level1.iso");
+    /// let iso = opticaldisc::iso::IsoFs::from_path(path).unwrap();
+    /// assert_eq!(iso.sessions().len(), 1);
+    /// assert_eq!(iso.sessions()[0].start, 0);
+    /// ```
+    pub fn sessions(&self) -> &[Session] {
+        &self.sessions
+    }
+
+    /// Get the index of the session being browsed.
+    pub fn current_session(&self) -> usize {
+        self.session
+    }
+
+    /// Get the policy used to match path components against file names.
+    pub fn lookup_policy(&self) -> LookupPolicy {
+        self.lookup
+    }
+
+    /// Change the policy used to match path components against file names.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let path = std::path::Path::new("static/iso/alpine.level1.iso");
+    /// use opticaldisc::iso::LookupPolicy;
+    ///
+    /// let mut iso = opticaldisc::iso::IsoFs::from_path(path).unwrap();
+    /// assert!(!iso.exists("/etc"));
+    /// iso.set_lookup_policy(LookupPolicy::exact().case_insensitive(true));
+    /// assert!(iso.exists("/etc"));
+    /// ```
+    pub fn set_lookup_policy(&mut self, policy: LookupPolicy) {
+        self.lookup = policy;
+    }
+
+    /// Read the El Torito boot catalog of the volume.
+    ///
+    /// # Errors
+    ///
+    /// * [`NoBootCatalog`](../error/enum.ErrorKind.html#variant.NoBootCatalog)
+    ///   when the volume has no El Torito boot record
+    /// * [`InvalidChecksum`](../error/enum.ErrorKind.html#variant.InvalidChecksum)
+    ///   when the validation entry of the catalog is corrupted
+    pub fn boot_catalog(&mut self) -> Result<BootCatalog> {
+        use self::constants::SECTOR_SIZE;
+
+        let location = match self.boot_catalog {
+            Some(location) => location as u64 * SECTOR_SIZE,
+            None => bail!(ErrorKind::NoBootCatalog),
+        };
+
+        // the size of the catalog is not recorded, so read sectors until
+        // the final section can be parsed
+        let mut bytes: Vec<u8> = Vec::new();
+        loop {
+            let start = bytes.len();
+            bytes.resize(start + SECTOR_SIZE as usize, 0);
+            self.handle.seek(::std::io::SeekFrom::Start(location + start as u64))?;
+            self.handle.read_exact(&mut bytes[start..])?;
+            match BootCatalog::parse(&bytes) {
+                Err(Error(ErrorKind::ParseIncomplete(_), _)) => continue,
+                other => return other,
+            }
+        }
+    }
+
+    /// Open the boot image described by a boot entry of the boot catalog.
+    ///
+    /// The size of emulated floppy and hard disk images is known from the
+    /// emulated media, but *no emulation* images only report the number of
+    /// sectors loaded by the BIOS, which may be less than the actual image.
+    pub fn open_boot_image<'a>(&'a mut self, entry: &BootEntry) -> Result<IsoFile<'a, H>> {
+        use self::constants::SECTOR_SIZE;
+        use self::eltorito::VIRTUAL_SECTOR_SIZE;
+
+        let start = entry.load_rba as u64 * SECTOR_SIZE;
+        let declared = entry.sector_count as u64 * VIRTUAL_SECTOR_SIZE;
+        let length = match entry.emulation {
+            Emulation::HardDisk => {
+                let mut mbr = [0; VIRTUAL_SECTOR_SIZE as usize];
+                self.handle.seek(::std::io::SeekFrom::Start(start))?;
+                self.handle.read_exact(&mut mbr)?;
+                eltorito::hard_disk_size(&mbr).unwrap_or(declared)
+            }
+            emulation => emulation.media_size().unwrap_or(declared),
+        };
+
+        Ok(IsoFile::from_extent(&mut self.handle, start, length))
+    }
+
+    /// Get the Volume Partition Descriptors of the volume, in order.
+    pub fn volume_partitions(&self) -> Vec<VolumePartitionDescriptor> {
+        self.descriptors()
+            .filter_map(|(_, descriptor)| match *descriptor {
+                VolumeDescriptor::Partition(ref vpd) => Some(vpd.as_ref().clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Open the contents of the partition described by a Volume Partition
+    /// Descriptor.
+    ///
+    /// The partition is read as raw bytes, since its contents are not part
+    /// of the file structure of the volume.
+    pub fn open_volume_partition<'a>(&'a mut self, vpd: &VolumePartitionDescriptor) -> Result<IsoFile<'a, H>> {
+        let block_size = self.volume_info.logical_block_size as u64;
+        let start = vpd.location as u64 * block_size;
+        let length = vpd.size as u64 * block_size;
+        Ok(IsoFile::from_extent(&mut self.handle, start, length))
+    }
+
+    /// Open the FAT filesystem stored in the boot image of a boot entry.
+    ///
+    /// The size of the image is read from the FAT boot sector, so that the
+    /// whole filesystem is available even for *no emulation* images. With
+    /// hard disk emulation, the filesystem of the first partition is opened.
+    pub fn open_boot_filesystem<'a>(&'a mut self, entry: &BootEntry) -> Result<FatFs<IsoFile<'a, H>>> {
+        use self::constants::SECTOR_SIZE;
+        use self::eltorito::VIRTUAL_SECTOR_SIZE;
+
+        let mut start = entry.load_rba as u64 * SECTOR_SIZE;
+        let mut sector = [0; VIRTUAL_SECTOR_SIZE as usize];
+        self.handle.seek(::std::io::SeekFrom::Start(start))?;
+        self.handle.read_exact(&mut sector)?;
+
+        if entry.emulation == Emulation::HardDisk {
+            if let Some((offset, _)) = eltorito::hard_disk_partition(&sector) {
+                start += offset;
+                self.handle.seek(::std::io::SeekFrom::Start(start))?;
+                self.handle.read_exact(&mut sector)?;
+            }
+        }
+
+        let length = BootSector::parse(&sector)?.size();
+        FatFs::new(IsoFile::from_extent(&mut self.handle, start, length))
+    }
+
+    /// Open the FAT filesystem of the EFI System Partition of the volume.
+    ///
+    /// The first boot entry of the catalog for the EFI platform is used.
+    ///
+    /// # Errors
+    ///
+    /// * [`NoBootCatalog`](../error/enum.ErrorKind.html#variant.NoBootCatalog)
+    ///   when the volume has no El Torito boot record
+    /// * [`NoEfiBootImage`](../error/enum.ErrorKind.html#variant.NoEfiBootImage)
+    ///   when the boot catalog has no entry for the EFI platform
+    pub fn open_efi_partition<'a>(&'a mut self) -> Result<FatFs<IsoFile<'a, H>>> {
+        let catalog = self.boot_catalog()?;
+        let entry = match catalog.entries().into_iter().find(|&(p, _)| p == Platform::Efi) {
+            Some((_, entry)) => entry.clone(),
+            None => bail!(ErrorKind::NoEfiBootImage),
+        };
+        self.open_boot_filesystem(&entry)
+    }
+
+    /// Read the partition tables recorded in the system area of the volume.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let path = std::path::Path::new("static/iso/alpine.level1.iso");
+    /// let mut iso = opticaldisc::iso::IsoFs::from_path(path).unwrap();
+    /// let system_area = iso.system_area().unwrap();
+    /// assert!(system_area.partitions().is_empty());
+    /// ```
+    pub fn system_area(&mut self) -> Result<SystemArea> {
+        SystemArea::read(&mut self.handle)
+    }
+
+    /// Get the files whose extent overlaps with the given partition.
+    ///
+    /// The whole hierarchy is traversed, which may take some time on large
+    /// volumes. The partitions of isohybrid images usually either span the
+    /// whole volume, or map to a single boot image file.
+    pub fn overlapping_files(&mut self, partition: &Partition) -> Result<Vec<Metadata>> {
+        let block_size = self.block_size as u64;
+        let mut files = Vec::new();
+        let mut directories = vec![self.root.clone()];
+
+        while let Some(directory) = directories.pop() {
+            for child in directory.children(&mut self.handle)? {
+                let record = &child.as_ref().record;
+                if record.is_dir {
+                    directories.push(child.clone());
+                } else if record.sections().iter().any(|section| {
+                    partition.overlaps(section.extent as u64 * block_size, section.data_length as u64)
+                }) {
+                    files.push(Metadata::from(child.clone()));
+                }
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Get the locations of the path tables of the browsed hierarchy.
+    pub fn path_table_locations(&self) -> PathTableLocations {
+        self.path_tables
+    }
+
+    /// Read the path table of the browsed hierarchy in the given byte order.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use opticaldisc::iso::ByteOrder;
+    /// # let path = std::path::Path::new("static/iso/alpine.level1.iso");
+    /// # let mut iso = opticaldisc::iso::IsoFs::from_path(path).unwrap();
+    /// let table = iso.path_table(ByteOrder::LittleEndian).unwrap();
+    /// let number = table.find("/ETC/APK").unwrap();
+    /// assert_eq!(table.path(number).unwrap(), std::path::Path::new("/ETC/APK"));
+    /// ```
+    pub fn path_table(&mut self, order: ByteOrder) -> Result<PathTable> {
+        let location = self.path_tables.table(order);
+        self.read_path_table(location, order)
+    }
+
+    /// Read the path table of `self.path_tables.size` bytes at `location`.
+    fn read_path_table(&mut self, location: u32, order: ByteOrder) -> Result<PathTable> {
+        // check the table fits in the image before allocating it
+        let start = location as u64 * self.block_size as u64;
+        let size = self.path_tables.size as u64;
+        if start + size > self.handle.seek(::std::io::SeekFrom::End(0))? {
+            bail!(::std::io::Error::new(
+                ::std::io::ErrorKind::UnexpectedEof,
+                "path table exceeds the image",
+            ));
+        }
+
+        let mut bytes = vec![0; size as usize];
+        self.handle.seek(::std::io::SeekFrom::Start(start))?;
+        self.handle.read_exact(&mut bytes)?;
+        PathTable::parse(&bytes, order, self.root.format)
+    }
+
+    /// Read all the path tables of the browsed hierarchy, and check they
+    /// all describe the same directories.
+    ///
+    /// # Errors
+    ///
+    /// * [`PathTableMismatch`](../error/enum.ErrorKind.html#variant.PathTableMismatch)
+    ///   when the path tables differ
+    pub fn check_path_tables(&mut self) -> Result<PathTable> {
+        let table = self.path_table(ByteOrder::LittleEndian)?;
+        let mut others = vec![(self.path_tables.m_table, ByteOrder::BigEndian)];
+        for &order in [ByteOrder::LittleEndian, ByteOrder::BigEndian].iter() {
+            if let Some(location) = self.path_tables.optional_table(order) {
+                others.push((location, order));
+            }
+        }
+        for (location, order) in others {
+            if self.read_path_table(location, order)? != table {
+                bail!(ErrorKind::PathTableMismatch);
+            }
+        }
+        Ok(table)
+    }
+
+    /// Get a directory node using the path table, reading only the first
+    /// sector of its extent.
+    ///
+    /// Returns `None` when the path cannot be resolved through the path
+    /// table, e.g. because it does not start with a directory, or because
+    /// Rock Ridge names are in use.
+    fn path_table_node(&mut self, path: &Path) -> Result<Option<Rc<Node>>> {
+        use std::path::Component::*;
+
+        if !self.path_table_lookup || (self.root.rock_ridge && self.root.susp.is_some()) {
+            return Ok(None);
+        }
+        if self.path_table.is_none() {
+            let table = self.path_table(ByteOrder::LittleEndian)?;
+            self.path_table = Some(table);
+        }
+
+        let mut names = Vec::new();
+        for component in path.components() {
+            match component {
+                RootDir | CurDir => (),
+                Normal(name) => match name.to_str() {
+                    Some(name) => names.push(name),
+                    None => return Ok(None),
+                },
+                ParentDir | Prefix(_) => return Ok(None),
+            }
+        }
+        if names.is_empty() {
+            return Ok(None);
+        }
+
+        // find the deepest directory of the path listed in the path table
+        let found = (1..=names.len()).rev().find_map(|depth| {
+            let table = self.path_table.as_ref().unwrap();
+            let number = table.lookup(names[..depth].join("/"), &self.lookup)?;
+            Some((depth, table.get(number)?.clone(), table.path(number)?))
+        });
+        let (depth, entry, dir_path) = match found {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+
+        let start = (entry.extent as u64 + entry.ear_length as u64) * self.block_size as u64;
+        let mut buffer = [0; self::constants::SECTOR_SIZE as usize];
+        self.handle.seek(::std::io::SeekFrom::Start(start))?;
+        self.handle.read_exact(&mut buffer)?;
+        let mut record = Record::parse(&buffer, self.root.format)?;
+        record.name = entry.name;
+
+        let mut node = Rc::new(self.root.create_descendant(dir_path, record));
+        for name in &names[depth..] {
+            node = node.as_ref().lookup(name, &self.lookup, &mut self.handle)?;
+        }
+        Ok(Some(node))
+    }
+
     /// Get a reference to a node from the ISO filesystem tree.
     fn node(&mut self, path: &Path) -> Result<Rc<Node>> {
+        if let Some(node) = self.path_table_node(path)? {
+            return Ok(node);
+        }
+
         let mut node: Rc<Node> = self.root.clone();
 
         for component in path.components() {
@@ -164,22 +672,32 @@ impl<H: Read + Seek> IsoFs<H> {
                 Prefix(_) => bail!(ErrorKind::Msg(String::from("what the fuck are you doing?"))),
                 CurDir => node,
                 RootDir => self.root.clone(),
-                Normal(name) => {
-                    let name_str = name.to_str().expect("not utf-8");
-                    node.as_ref().child(name_str, &mut self.handle)?
-                }
-                ParentDir => self.node(node.as_ref().path.parent().expect("no parent"))?,
+                Normal(name) => match name.to_str() {
+                    Some(name) => node.as_ref().lookup(name, &self.lookup, &mut self.handle)?,
+                    // names that are not valid UTF-8 can only match the
+                    // identifiers of records as they were recorded
+                    None => node.as_ref().raw_child(&os_bytes(name), &mut self.handle)?,
+                },
+                // relocated directories are only grafted at their logical
+                // location when the `PL` entry of their `..` entry points
+                // back to it, so their path parent is that directory
+                ParentDir => match node.as_ref().path.parent() {
+                    Some(parent) => self.node(parent)?,
+                    None => self.root.clone(),
+                },
             }
         }
 
         Ok(node)
     }
 
-    /// Get an iterator over a directory content.
+    /// Get the contents of a directory, in on-disc order.
     ///
-    /// The directory contents are loaded before the [`ReadDir`] iterator is
-    /// created if they were not already. This allows the iterator to outlive
-    /// the reference to the `IsoFs`.
+    /// The directory contents are loaded and cached if they were not already,
+    /// which allows the contents to outlive the reference to the `IsoFs`.
+    /// See [`read_dir_iter`](#method.read_dir_iter) to stream the contents
+    /// of a directory instead. Hidden entries are skipped if the
+    /// `IsoFs` was opened with [`Options::hidden`] set to `false`.
     ///
     /// # Errors
     ///
@@ -201,16 +719,86 @@ impl<H: Read + Seek> IsoFs<H> {
     ///    }
     /// }
     /// ```
+    ///
+    /// [`Options::hidden`]: struct.Options.html#method.hidden
     pub fn read_dir<P: AsRef<Path>>(&mut self, path: P) -> Result<Vec<Metadata>> {
         let node = self.node(path.as_ref())?;
+        let hidden = self.hidden;
         let contents = node.as_ref()
             .children(&mut self.handle)?
             .into_iter()
+            .filter(|child| hidden || !child.record.is_hidden)
             .map(Metadata::from)
             .collect();
         Ok(contents)
     }
 
+    /// Get a streaming iterator over a directory content, in on-disc order.
+    ///
+    /// Unlike [`read_dir`](#method.read_dir), the directory records are
+    /// parsed as the iterator advances, and are not added to the cache of
+    /// the `IsoFs`, which avoids loading huge directories at once.
+    ///
+    /// # Errors
+    ///
+    /// * [`NotFound`](../error/enum.ErrorKind.html#variant.NotFound)
+    ///   when the resource could not be found
+    /// * [`DirectoryExpected`](../error/enum.ErrorKind.html#variant.DirectoryExpected)
+    ///   when the resource is not a directory
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let path = std::path::Path::new("static/iso/alpine.level1.iso");
+    /// # let mut iso = opticaldisc::iso::IsoFs::from_path(path).unwrap();
+    /// let names = iso.read_dir_iter("/ETC/APK").unwrap()
+    ///     .map(|entry| entry.map(|meta| meta.name().to_owned()))
+    ///     .collect::<Result<Vec<String>, _>>()
+    ///     .unwrap();
+    /// assert!(names.contains(&String::from("ARCH")));
+    /// ```
+    pub fn read_dir_iter<'a, P: AsRef<Path>>(&'a mut self, path: P) -> Result<ReadDir<'a, H>> {
+        let node = self.node(path.as_ref())?;
+        if !node.record.is_dir {
+            bail!(ErrorKind::DirectoryExpected);
+        }
+        Ok(ReadDir::new(node, &mut self.handle, self.hidden))
+    }
+
+    /// Get all the versions of the file located at the given path, from
+    /// the highest to the lowest version.
+    ///
+    /// Other methods of `IsoFs` use the highest version of a file, unless
+    /// the path ends with an explicit version number (e.g. `NAME.EXT;2`).
+    ///
+    /// # Errors
+    ///
+    /// * [`NotFound`](../error/enum.ErrorKind.html#variant.NotFound)
+    ///   when the resource could not be found
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let path = std::path::Path::new("static/iso/alpine.level1.iso");
+    /// # let mut iso = opticaldisc::iso::IsoFs::from_path(path).unwrap();
+    /// let versions = iso.versions("/ETC/APK/ARCH").unwrap();
+    /// assert_eq!(versions.len(), 1);
+    /// assert_eq!(versions[0].version(), Some(1));
+    /// ```
+    pub fn versions<P: AsRef<Path>>(&mut self, path: P) -> Result<Vec<Metadata>> {
+        let path = path.as_ref();
+        let name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) => name,
+            None => bail!(ErrorKind::NotFound(path.to_path_buf())),
+        };
+        let parent = self.node(path.parent().unwrap_or_else(|| Path::new("/")))?;
+        let versions = parent.versions(name, &mut self.handle)?;
+        if versions.is_empty() {
+            bail!(ErrorKind::NotFound(path.to_path_buf()));
+        }
+        Ok(versions.into_iter().map(Metadata::from).collect())
+    }
+
     /// Get metadata about a resource located at the given path.
     ///
     /// # Errors
@@ -268,12 +856,42 @@ impl<H: Read + Seek> IsoFs<H> {
         self.node(path.as_ref()).is_ok()
     }
 
+    /// Get the system use entries of the resource located at the given path.
+    ///
+    /// See [`Metadata::system_use_entries`] for more details.
+    ///
+    /// [`Metadata::system_use_entries`]: struct.Metadata.html#method.system_use_entries
+    pub fn system_use_entries<P: AsRef<Path>>(&mut self, path: P) -> Result<Vec<susp::Entry>> {
+        let metadata = self.metadata(path)?;
+        metadata.system_use_entries(self)
+    }
+
+    /// Get the Extended Attribute Record of the resource located at the
+    /// given path, if it has one.
+    ///
+    /// See [`Metadata::extended_attributes`] for more details.
+    ///
+    /// [`Metadata::extended_attributes`]: struct.Metadata.html#method.extended_attributes
+    pub fn extended_attributes<P: AsRef<Path>>(&mut self, path: P) -> Result<Option<ExtendedAttributes>> {
+        let metadata = self.metadata(path)?;
+        metadata.extended_attributes(self)
+    }
+
     /// Open the file located at the given path.
     ///
     /// The file can be kept open as long as you can keep a mutable reference
     /// to the `IsoFs`. This avoids the handle by being modified by both the
     /// file and the filesystem instances.
     ///
+    /// Files compressed with zisofs, as marked by a Rock Ridge `ZF` entry,
+    /// are decompressed transparently.
+    ///
+    /// # Errors
+    ///
+    /// * [`VolumeNotFound`](../error/enum.ErrorKind.html#variant.VolumeNotFound)
+    ///   when the file is recorded on another volume of a volume set, in
+    ///   which case it can be read through an [`IsoVolumeSet`](struct.IsoVolumeSet.html)
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -286,10 +904,21 @@ impl<H: Read + Seek> IsoFs<H> {
     /// ```
     pub fn open_file<'a, P: AsRef<Path>>(&'a mut self, path: P) -> Result<IsoFile<'a, H>> {
         let node = self.node(path.as_ref())?;
-        let start = node.record.extent * self.block_size as u32;
-
-        println!("{:?}", node.record);
+        self.check_volume(&node.record)?;
+        IsoFile::new(&mut self.handle, &node.record, self.block_size).map_err(Error::from)
+    }
 
-        IsoFile::new(&mut self.handle, start, node.record.data_length).map_err(Error::from)
+    /// Check that the extent of a record is stored on this volume.
+    ///
+    /// Sequence numbers are only checked for volumes of a multi-volume set,
+    /// since single volumes often leave them unset.
+    pub(in iso) fn check_volume(&self, record: &Record) -> Result<()> {
+        if self.volume_info.volume_set_size > 1
+            && record.seq_number != 0
+            && record.seq_number != self.volume_info.volume_sequence_number
+        {
+            bail!(ErrorKind::VolumeNotFound(record.seq_number));
+        }
+        Ok(())
     }
 }
@@ -1,4 +1,5 @@
 use std::cell::RefCell;
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::io::Read;
 use std::io::Seek;
@@ -10,66 +11,242 @@ use super::super::error::Error;
 use super::super::error::ErrorKind;
 use super::super::error::Result;
 
+use super::options::LookupPolicy;
+use super::record::Format;
 use super::record::Record;
+use super::readdir::Records;
 use super::constants::SECTOR_SIZE;
 
+/// The children of a directory node.
+#[derive(Default)]
+pub(in iso) struct Children {
+    /// The names of the children, in on-disc order.
+    pub names: Vec<String>,
+    /// All the versions of each name, from the highest to the lowest.
+    pub versions: HashMap<String, Vec<Rc<Node>>>,
+}
+
 /// A node from an ISO-9660 filesystem.
 pub(in iso) struct Node {
     pub path: PathBuf,
     pub record: Record,
-    pub contents: RefCell<Option<HashMap<String, Rc<Node>>>>,
+    pub format: Format,
+    pub rock_ridge: bool,
+    pub susp: Option<u8>,
+    pub contents: RefCell<Option<Children>>,
+    pub associated: Option<Rc<Node>>,
 }
 
 impl Node {
-    /// Create a new root node from the given record (used in PVD).
-    pub(in iso) fn create_root(record: Record) -> Self {
+    /// Create a new root node from the given record (used in PVD and SVD).
+    ///
+    /// `susp` is the number of bytes to skip in system use areas if the
+    /// SUSP is in use, and Rock Ridge entries are decoded from the children
+    /// records only if it is and `rock_ridge` is `true`.
+    pub(in iso) fn create_root(
+        record: Record,
+        format: Format,
+        rock_ridge: bool,
+        susp: Option<u8>,
+    ) -> Self {
         Self {
             path: PathBuf::from("/"),
-            record: record,
+            record,
+            format,
+            rock_ridge,
+            susp,
             contents: RefCell::new(None),
+            associated: None,
         }
     }
 
     /// Create a child node from the given record (to add to the contents map).
-    fn create_child(&self, record: Record) -> Self {
+    pub(in iso) fn create_child(&self, record: Record) -> Self {
+        Self {
+            path: self.path.join(Self::record_name(&record)),
+            record,
+            format: self.format,
+            rock_ridge: self.rock_ridge,
+            susp: self.susp,
+            contents: RefCell::new(None),
+            associated: None,
+        }
+    }
+
+    /// Create a node for a record found at `path` without walking the
+    /// hierarchy from `self` (used with path tables).
+    pub(in iso) fn create_descendant(&self, path: PathBuf, record: Record) -> Self {
         Self {
-            path: self.path.join(&record.name),
-            record: record,
+            path,
+            record,
+            format: self.format,
+            rock_ridge: self.rock_ridge,
+            susp: self.susp,
             contents: RefCell::new(None),
+            associated: None,
+        }
+    }
+
+    /// Get the name of a record, preferring its Rock Ridge name if any.
+    fn record_name(record: &Record) -> &str {
+        record
+            .rock_ridge
+            .as_ref()
+            .and_then(|rr| rr.name.as_ref())
+            .unwrap_or(&record.name)
+    }
+
+    /// Get the name of `self`.
+    pub(in iso) fn name(&self) -> &str {
+        Self::record_name(&self.record)
+    }
+
+    /// Get the name of `self` before it was decoded, i.e. its identifier
+    /// without the version number.
+    ///
+    /// Rock Ridge and Joliet names are returned encoded in UTF-8.
+    pub(in iso) fn raw_name(&self) -> &[u8] {
+        let identifier = &self.record.identifier[..];
+        match self.record.rock_ridge.as_ref().and_then(|rr| rr.name.as_ref()) {
+            Some(name) => name.as_bytes(),
+            None if self.format == Format::Joliet => self.record.name.as_bytes(),
+            None if self.record.version.is_none() => identifier,
+            None => {
+                let sep = identifier.iter().rposition(|&b| b == b';').unwrap_or(identifier.len());
+                let base = &identifier[..sep];
+                base.strip_suffix(b".").unwrap_or(base)
+            }
         }
     }
 
     /// Find the child of given `name`, using `handle` to parse contents of
     /// directories that are yet unknown.
+    ///
+    /// The highest version of the child is returned, unless `name` ends
+    /// with a version number (e.g. `NAME.EXT;2`).
     pub(in iso) fn child<H>(&self, name: &str, handle: &mut H) -> Result<Rc<Self>>
+    where
+        H: Read + Seek,
+    {
+        if let Some(child) = self.versions(name, handle)?.into_iter().next() {
+            return Ok(child);
+        }
+        if let Some((base, version)) = split_version(name) {
+            let versions = self.versions(base, handle)?;
+            if let Some(child) = versions.into_iter().find(|c| c.record.version == Some(version)) {
+                return Ok(child);
+            }
+        }
+        Err(Error::from(ErrorKind::NotFound(self.path.join(name))))
+    }
+
+    /// Find the child with the given undecoded `name`, compared to the raw
+    /// names of the children, or to their identifiers to select a version.
+    pub(in iso) fn raw_child<H>(&self, name: &[u8], handle: &mut H) -> Result<Rc<Self>>
+    where
+        H: Read + Seek,
+    {
+        self.load_children(handle)?;
+        let contents = self.contents.borrow();
+        let children = contents.as_ref().unwrap();
+        children
+            .names
+            .iter()
+            .flat_map(|n| children.versions[n].iter())
+            .find(|c| c.raw_name() == name || c.record.identifier == name)
+            .cloned()
+            .ok_or_else(|| {
+                let path = self.path.join(String::from_utf8_lossy(name).as_ref());
+                Error::from(ErrorKind::NotFound(path))
+            })
+    }
+
+    /// Find the child of given `name`, matching names under `policy` if no
+    /// child has exactly the requested name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AmbiguousPath`] error if several children match `name`
+    /// under `policy`.
+    ///
+    /// [`AmbiguousPath`]: ../error/enum.ErrorKind.html#variant.AmbiguousPath
+    pub(in iso) fn lookup<H>(&self, name: &str, policy: &LookupPolicy, handle: &mut H) -> Result<Rc<Self>>
+    where
+        H: Read + Seek,
+    {
+        let err = match self.child(name, handle) {
+            Err(Error(ErrorKind::NotFound(path), state)) if !policy.is_exact() => {
+                Error(ErrorKind::NotFound(path), state)
+            }
+            other => return other,
+        };
+
+        // an explicit version is honoured even when versions are stripped
+        let (base, version) = match split_version(name) {
+            Some((base, version)) => (base, Some(version)),
+            None => (name, None),
+        };
+        let key = policy.normalize(base);
+
+        let contents = self.contents.borrow();
+        let children = contents.as_ref().unwrap();
+        let matches: Vec<&String> = children
+            .names
+            .iter()
+            .filter(|n| policy.normalize(n) == key)
+            .collect();
+
+        match matches.len() {
+            0 => Err(err),
+            1 => {
+                let versions = &children.versions[matches[0]];
+                match version {
+                    None => Ok(versions[0].clone()),
+                    Some(v) => versions
+                        .iter()
+                        .find(|c| c.record.version == Some(v))
+                        .cloned()
+                        .ok_or(err),
+                }
+            }
+            _ => {
+                let names = matches.into_iter().cloned().collect();
+                Err(Error::from(ErrorKind::AmbiguousPath(self.path.join(name), names)))
+            }
+        }
+    }
+
+    /// Get all the versions of the child of given `name`, from the highest
+    /// to the lowest version.
+    pub(in iso) fn versions<H>(&self, name: &str, handle: &mut H) -> Result<Vec<Rc<Self>>>
     where
         H: Read + Seek,
     {
         if self.record.is_dir {
             self.load_children(handle)?;
-            match self.contents.borrow().as_ref().unwrap().get(name) {
-                Some(rc) => Ok(rc.clone()),
-                None => Err(Error::from(ErrorKind::NotFound(self.path.join(name)))),
-            }
+            let contents = self.contents.borrow();
+            Ok(contents.as_ref().unwrap().versions.get(name).cloned().unwrap_or_default())
         } else {
             Err(Error::from_kind(ErrorKind::DirectoryExpected))
         }
     }
 
+    /// Get all the children of `self` in on-disc order, parsing them if
+    /// needed.
     ///
+    /// Only the highest version of each child is returned.
     pub(in iso) fn children<H>(&self, handle: &mut H) -> Result<Vec<Rc<Self>>>
     where
         H: Read + Seek,
     {
         self.load_children(handle)?;
-        let contents = self.contents
-            .borrow()
-            .as_ref()
-            .unwrap()
-            .values()
-            .map(Clone::clone)
-            .collect();
-        Ok(contents)
+        let contents = self.contents.borrow();
+        let children = contents.as_ref().unwrap();
+        Ok(children
+            .names
+            .iter()
+            .map(|name| children.versions[name][0].clone())
+            .collect())
     }
 
     /// Load the children directory records if they are still unknown.
@@ -86,6 +263,60 @@ impl Node {
         }
     }
 
+    /// Check whether `record` may be the directory where Rock Ridge
+    /// relocates directories nested too deep.
+    ///
+    /// It is only hidden from the Rock Ridge view if it actually holds
+    /// relocated directories, which is checked by the caller.
+    pub(in iso) fn is_relocation_directory(&self, record: &Record) -> bool {
+        self.rock_ridge
+            && self.susp.is_some()
+            && record.is_dir
+            && self.path.parent().is_none()
+            && matches!(
+                Self::record_name(record).to_lowercase().as_str(),
+                "rr_moved" | ".rr_moved"
+            )
+    }
+
+    /// Get the record of a relocated directory, given the record of the
+    /// placeholder standing for it in its logical parent.
+    ///
+    /// The record is read from the `.` entry of the relocated directory,
+    /// but keeps the name of the placeholder. The `PL` entry of its `..`
+    /// entry must point back to `self`: otherwise, the placeholder is
+    /// returned unchanged.
+    pub(in iso) fn relocated_record<H>(&self, placeholder: Record, location: u32, handle: &mut H) -> Result<Record>
+    where
+        H: Read + Seek,
+    {
+        use super::record::parser::record;
+        use super::rockridge::RockRidge;
+        use super::susp;
+
+        let mut buffer = [0; SECTOR_SIZE as usize];
+        handle.seek(SeekFrom::Start(location as u64 * SECTOR_SIZE))?;
+        handle.read_exact(&mut buffer)?;
+
+        let (rem, mut dot) = record(&buffer, self.format)?;
+        let (_, dotdot) = record(rem, self.format)?;
+        let entries = susp::read(&dotdot.system_use, self.susp.unwrap_or(0), handle)?;
+        match RockRidge::from_entries(&entries).and_then(|rr| rr.parent_link) {
+            Some(location) if location == self.record.extent => (),
+            _ => return Ok(placeholder),
+        }
+
+        let entries = susp::read(&dot.system_use, self.susp.unwrap_or(0), handle)?;
+        let name = placeholder.rock_ridge.and_then(|rr| rr.name);
+        let mut rock_ridge = RockRidge::from_entries(&entries).unwrap_or_default();
+        rock_ridge.name = name;
+
+        dot.name = placeholder.name;
+        dot.version = placeholder.version;
+        dot.rock_ridge = Some(rock_ridge);
+        Ok(dot)
+    }
+
     /// Parse the children records of `self`.
     ///
     /// Expects `self` to be a directory, or bad things could occur.
@@ -93,42 +324,290 @@ impl Node {
     where
         H: Read + Seek,
     {
-        use super::record::parser::record;
-
-        let mut total: u64 = 0;
-        let mut offset: usize;
-        let mut contents = HashMap::new();
-        let mut buffer = [255; SECTOR_SIZE as usize];
-
-        // seek to the initial extent position
-        handle.seek(SeekFrom::Start(self.record.extent as u64 * SECTOR_SIZE))?;
-
-        // a sector with directory records starts with the length of the first
-        // directory record, which can never have a length of 0
-        'sector: while total < self.record.data_length as u64 {
-            // read the next sector and reset the buffer offset
-            offset = 0;
-            handle.read_exact(&mut buffer)?;
-            total += SECTOR_SIZE;
-            // parse records while there are more records to be found
-            'record: while buffer[offset] != 0 {
-                // parse the next record and advance the buffer cursor
-                let (rem, record) = record(&buffer[offset..])?;
-                offset = SECTOR_SIZE as usize - rem.len();
-
-                // check the record is not another directory, and add it to
-                // the directory contents hashmap
-                if record.name == "\0" && record.extent != self.record.extent {
-                    break 'sector;
-                } else if record.name != "\0" && record.name != "\x01" {
-                    let name = record.name.clone();
-                    contents.insert(name, Rc::new(self.create_child(record)));
-                }
+        let mut children = Children::default();
+        for child in Records::new(self, handle) {
+            let child = child?;
+            let name = child.name().to_owned();
+            if !children.versions.contains_key(&name) {
+                children.names.push(name.clone());
             }
+            let versions = children.versions.entry(name).or_insert_with(Vec::new);
+            versions.push(Rc::new(child));
+            versions.sort_by_key(|node| Reverse(node.record.version));
         }
 
         // replace the directory contents with the parsed children
-        self.contents.replace(Some(contents));
+        self.contents.replace(Some(children));
         Ok(())
     }
 }
+
+/// Split the version number from a name ending with `;N`, also removing
+/// the `.` separator of names without an extension.
+fn split_version(name: &str) -> Option<(&str, u8)> {
+    let sep = name.rfind(';')?;
+    let version = name[sep + 1..].parse().ok()?;
+    let base = &name[..sep];
+    Some((base.strip_suffix('.').unwrap_or(base), version))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::io::Cursor;
+    use std::io::Read;
+
+    use utils::charsets::Charset;
+
+    use super::super::super::error::Error;
+    use super::super::super::error::ErrorKind;
+    use super::super::constants::SECTOR_SIZE;
+    use super::super::file::IsoFile;
+    use super::super::options::LookupPolicy;
+    use super::super::readdir::ReadDir;
+    use super::super::record::Format;
+    use super::super::record::Record;
+    use super::Node;
+
+    /// Build a directory record with the given identifier and flags.
+    fn record(id: &[u8], extent: u32, length: u32, flags: u8) -> Vec<u8> {
+        let mut raw = vec![33 + id.len() as u8 + id.len().is_multiple_of(2) as u8, 0];
+        raw.extend_from_slice(&extent.to_le_bytes());
+        raw.extend_from_slice(&extent.to_be_bytes());
+        raw.extend_from_slice(&length.to_le_bytes());
+        raw.extend_from_slice(&length.to_be_bytes());
+        raw.extend_from_slice(b"\x76\x0B\x0D\x09\x23\x2D\x00");
+        raw.extend_from_slice(&[flags, 0, 0, 1, 0, 0, 1, id.len() as u8]);
+        raw.extend_from_slice(id);
+        raw.resize(raw[0] as usize, 0);
+        raw
+    }
+
+    /// Build an image with a directory at sector 0 holding a multi-extent
+    /// file, the sections of which are stored at sectors 1, 3 and 2.
+    fn image() -> Vec<u8> {
+        let mut img = vec![0; 4 * SECTOR_SIZE as usize];
+        let mut dir = Vec::new();
+        dir.extend(record(b"\0", 0, 2048, 0x02));
+        dir.extend(record(b"\x01", 0, 2048, 0x02));
+        dir.extend(record(b"BIG.;1", 1, 2048, 0x80));
+        dir.extend(record(b"BIG.;1", 3, 2048, 0x80));
+        dir.extend(record(b"BIG.;1", 2, 5, 0x00));
+        dir.extend(record(b"SMALL.;1", 2, 5, 0x00));
+        img[..dir.len()].copy_from_slice(&dir);
+        for (sector, byte) in [(1, b'a'), (2, b'b'), (3, b'c')].iter() {
+            let start = sector * SECTOR_SIZE as usize;
+            for x in img[start..start + SECTOR_SIZE as usize].iter_mut() {
+                *x = *byte;
+            }
+        }
+        img
+    }
+
+    #[test]
+    fn test_multi_extent() {
+        let mut handle = Cursor::new(image());
+        let root_record = Record::parse(&record(b"\0", 0, 2048, 0x02), Format::Iso(Charset::Auto)).unwrap();
+        let root = Node::create_root(root_record, Format::Iso(Charset::Auto), false, None);
+
+        let children = root.children(&mut handle).unwrap();
+        assert_eq!(children.len(), 2);
+
+        let big = root.child("BIG", &mut handle).unwrap();
+        assert_eq!(big.record.sections().len(), 3);
+        assert_eq!(big.record.length(), 2 * 2048 + 5);
+        assert!(!big.record.multi_extent);
+
+        let mut content = Vec::new();
+        IsoFile::new(&mut handle, &big.record, 2048)
+            .unwrap()
+            .read_to_end(&mut content)
+            .unwrap();
+        assert_eq!(content.len(), 2 * 2048 + 5);
+        assert!(content[..2048].iter().all(|&b| b == b'a'));
+        assert!(content[2048..4096].iter().all(|&b| b == b'c'));
+        assert_eq!(&content[4096..], b"bbbbb");
+
+        let small = root.child("SMALL", &mut handle).unwrap();
+        assert_eq!(small.record.length(), 5);
+    }
+
+    #[test]
+    fn test_multi_extent_interrupted() {
+        let mut img = image();
+        let mut dir = Vec::new();
+        dir.extend(record(b"\0", 0, 2048, 0x02));
+        dir.extend(record(b"\x01", 0, 2048, 0x02));
+        dir.extend(record(b"BIG.;1", 1, 2048, 0x80));
+        dir.extend(record(b"SMALL.;1", 2, 5, 0x00));
+        dir.extend(record(b"LAST.;1", 3, 2048, 0x80));
+        dir.extend(record(b"LAST.;1", 1, 2048, 0x80));
+        img[..2048].iter_mut().for_each(|b| *b = 0);
+        img[..dir.len()].copy_from_slice(&dir);
+
+        let mut handle = Cursor::new(img);
+        let root_record = Record::parse(&record(b"\0", 0, 2048, 0x02), Format::Iso(Charset::Auto)).unwrap();
+        let root = Node::create_root(root_record, Format::Iso(Charset::Auto), false, None);
+        let names = root
+            .children(&mut handle)
+            .unwrap()
+            .iter()
+            .map(|n| n.name().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["BIG", "SMALL", "LAST"]);
+
+        // a group interrupted by another record keeps the sections read so far
+        let big = root.child("BIG", &mut handle).unwrap();
+        assert!(big.record.multi_extent);
+        assert_eq!(big.record.sections().len(), 1);
+        assert_eq!(big.record.length(), 2048);
+        let small = root.child("SMALL", &mut handle).unwrap();
+        assert_eq!(small.record.length(), 5);
+
+        // so does a group interrupted by the end of the directory
+        let last = root.child("LAST", &mut handle).unwrap();
+        assert!(last.record.multi_extent);
+        assert_eq!(last.record.sections().len(), 2);
+        assert_eq!(last.record.length(), 2 * 2048);
+    }
+
+    #[test]
+    fn test_associated() {
+        let mut img = image();
+        let mut dir = Vec::new();
+        dir.extend(record(b"\0", 0, 2048, 0x02));
+        dir.extend(record(b"\x01", 0, 2048, 0x02));
+        dir.extend(record(b"FILE.;1", 1, 3, 0x04));
+        dir.extend(record(b"FILE.;1", 2, 5, 0x01));
+        dir.extend(record(b"ORPHAN.;1", 3, 2, 0x04));
+        dir.extend(record(b"OTHER.;1", 3, 2, 0x00));
+        img[..2048].iter_mut().for_each(|b| *b = 0);
+        img[..dir.len()].copy_from_slice(&dir);
+
+        let mut handle = Cursor::new(img);
+        let root_record = Record::parse(&record(b"\0", 0, 2048, 0x02), Format::Iso(Charset::Auto)).unwrap();
+        let root = Node::create_root(root_record, Format::Iso(Charset::Auto), false, None);
+
+        // associated files without a matching file keep their position
+        let names = root
+            .children(&mut handle)
+            .unwrap()
+            .iter()
+            .map(|n| n.name().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["FILE", "ORPHAN", "OTHER"]);
+
+        let file = root.child("FILE", &mut handle).unwrap();
+        assert!(file.record.is_hidden);
+        assert!(!file.record.is_associated);
+        let fork = file.associated.as_ref().unwrap();
+        assert!(fork.record.is_associated);
+        assert_eq!(fork.record.extent, 1);
+
+        let orphan = root.child("ORPHAN", &mut handle).unwrap();
+        assert!(orphan.record.is_associated);
+    }
+
+    #[test]
+    fn test_versions() {
+        let mut img = image();
+        let mut dir = Vec::new();
+        dir.extend(record(b"\0", 0, 2048, 0x02));
+        dir.extend(record(b"\x01", 0, 2048, 0x02));
+        dir.extend(record(b"FILE.TXT;1", 1, 3, 0x00));
+        dir.extend(record(b"FILE.TXT;3", 3, 3, 0x00));
+        dir.extend(record(b"FILE.TXT;2", 2, 3, 0x00));
+        img[..2048].iter_mut().for_each(|b| *b = 0);
+        img[..dir.len()].copy_from_slice(&dir);
+
+        let mut handle = Cursor::new(img);
+        let root_record = Record::parse(&record(b"\0", 0, 2048, 0x02), Format::Iso(Charset::Auto)).unwrap();
+        let root = Node::create_root(root_record, Format::Iso(Charset::Auto), false, None);
+        assert_eq!(root.children(&mut handle).unwrap().len(), 1);
+
+        let versions = root.versions("FILE.TXT", &mut handle).unwrap();
+        let numbers = versions.iter().map(|n| n.record.version).collect::<Vec<_>>();
+        assert_eq!(numbers, vec![Some(3), Some(2), Some(1)]);
+
+        let latest = root.child("FILE.TXT", &mut handle).unwrap();
+        assert_eq!(latest.record.extent, 3);
+        let second = root.child("FILE.TXT;2", &mut handle).unwrap();
+        assert_eq!(second.record.extent, 2);
+        assert!(root.child("FILE.TXT;4", &mut handle).is_err());
+
+        // the highest version of a name is streamed, as in `children`
+        let root = ::std::rc::Rc::new(root);
+        let streamed = ReadDir::new(root, &mut handle, true)
+            .map(|entry| entry.unwrap().version())
+            .collect::<Vec<_>>();
+        assert_eq!(streamed, vec![Some(3)]);
+    }
+
+    #[test]
+    fn test_lookup() {
+        let mut img = image();
+        let mut dir = Vec::new();
+        dir.extend(record(b"\0", 0, 2048, 0x02));
+        dir.extend(record(b"\x01", 0, 2048, 0x02));
+        dir.extend(record(b"MY_FILE.TXT;1", 1, 3, 0x00));
+        dir.extend(record(b"MY_FILE.TXT;2", 2, 3, 0x00));
+        dir.extend(record(b"A_B;1", 1, 3, 0x00));
+        dir.extend(record(b"A-B;1", 2, 3, 0x00));
+        img[..2048].iter_mut().for_each(|b| *b = 0);
+        img[..dir.len()].copy_from_slice(&dir);
+
+        let mut handle = Cursor::new(img);
+        let root_record = Record::parse(&record(b"\0", 0, 2048, 0x02), Format::Iso(Charset::Auto)).unwrap();
+        let root = Node::create_root(root_record, Format::Iso(Charset::Auto), false, None);
+
+        let exact = LookupPolicy::exact();
+        assert!(root.lookup("my_file.txt", &exact, &mut handle).is_err());
+
+        let nocase = LookupPolicy::exact().case_insensitive(true);
+        let file = root.lookup("my_file.txt", &nocase, &mut handle).unwrap();
+        assert_eq!(file.record.version, Some(2));
+        let file = root.lookup("my_file.txt;1", &nocase, &mut handle).unwrap();
+        assert_eq!(file.record.version, Some(1));
+        assert!(root.lookup("my_file.txt.", &nocase, &mut handle).is_err());
+
+        let relaxed = LookupPolicy::relaxed();
+        let file = root.lookup("my-file.txt", &relaxed, &mut handle).unwrap();
+        assert_eq!(file.record.version, Some(2));
+        let file = root.lookup("my-file.txt;1", &relaxed, &mut handle).unwrap();
+        assert_eq!(file.record.version, Some(1));
+        assert!(root.lookup("my-file.txt;3", &relaxed, &mut handle).is_err());
+
+        // an exact match is never ambiguous
+        let file = root.lookup("A-B", &relaxed, &mut handle).unwrap();
+        assert_eq!(file.record.extent, 2);
+        match root.lookup("a-b", &relaxed, &mut handle) {
+            Err(Error(ErrorKind::AmbiguousPath(_, names), _)) => {
+                assert_eq!(names, vec!["A_B", "A-B"])
+            }
+            other => panic!("expected an ambiguity, got {:?}", other.map(|n| n.path.clone())),
+        }
+    }
+
+    #[test]
+    fn test_split_version() {
+        assert_eq!(super::split_version("APK.;1"), Some(("APK", 1)));
+        assert_eq!(super::split_version("FILE.TXT;12"), Some(("FILE.TXT", 12)));
+        assert_eq!(super::split_version("FILE.TXT"), None);
+        assert_eq!(super::split_version("FILE;X"), None);
+    }
+
+    #[test]
+    fn test_ear_skipped() {
+        let mut handle = Cursor::new(image());
+        let mut raw = record(b"EAR.;1", 1, 5, 0x00);
+        raw[1] = 1;
+        let record = Record::parse(&raw, Format::Iso(Charset::Auto)).unwrap();
+
+        let mut content = Vec::new();
+        IsoFile::new(&mut handle, &record, 2048)
+            .unwrap()
+            .read_to_end(&mut content)
+            .unwrap();
+        assert_eq!(content, b"bbbbb");
+    }
+}
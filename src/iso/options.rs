@@ -0,0 +1,274 @@
+use utils::charsets::Charset;
+
+/// The directory hierarchy browsed by an [`IsoFs`].
+///
+/// [`IsoFs`]: struct.IsoFs.html
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Tree {
+    /// The hierarchy of the Primary Volume Descriptor.
+    #[default]
+    Primary,
+    /// The Joliet hierarchy of a Supplementary Volume Descriptor, which
+    /// stores long file names encoded in UCS-2.
+    Joliet,
+    /// The hierarchy of an Enhanced Volume Descriptor (ISO-9660:1999),
+    /// which stores long file names without version numbers.
+    Enhanced,
+}
+
+/// The policy used to match path components against the names of a
+/// directory's children.
+///
+/// Names are always compared exactly first, so the policy is only used
+/// when no child has exactly the requested name. The default policy
+/// only accepts exact matches.
+///
+/// # Example
+///
+/// ```rust
+/// use opticaldisc::iso::{IsoFs, LookupPolicy, Options};
+///
+/// let file = std::fs::File::open("static/iso/alpine.level1.iso").unwrap();
+/// let options = Options::new().lookup(LookupPolicy::relaxed());
+/// let mut iso = IsoFs::with_options(file, options).unwrap();
+/// assert!(iso.is_file("etc/apk/arch"));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LookupPolicy {
+    case_insensitive: bool,
+    strip_versions: bool,
+    d_characters: bool,
+}
+
+impl LookupPolicy {
+    /// Create a policy only accepting exact matches.
+    pub fn exact() -> Self {
+        Self::default()
+    }
+
+    /// Create a policy enabling all the normalizations.
+    pub fn relaxed() -> Self {
+        Self {
+            case_insensitive: true,
+            strip_versions: true,
+            d_characters: true,
+        }
+    }
+
+    /// Compare names without regard to case.
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    /// Ignore trailing dots and version numbers (e.g. `ARCH.;1`), resolving
+    /// to the highest version of a file unless the requested name ends with
+    /// an explicit version.
+    pub fn strip_versions(mut self, strip_versions: bool) -> Self {
+        self.strip_versions = strip_versions;
+        self
+    }
+
+    /// Map characters outside of the ISO-9660 d-characters to `_`, the
+    /// way most mastering tools do, after converting them to upper-case
+    /// (e.g. `my-file.txt` matches `MY_FILE.TXT`).
+    pub fn d_characters(mut self, d_characters: bool) -> Self {
+        self.d_characters = d_characters;
+        self
+    }
+
+    /// Check whether the policy only accepts exact matches.
+    pub fn is_exact(&self) -> bool {
+        !(self.case_insensitive || self.strip_versions || self.d_characters)
+    }
+
+    /// Normalize a name so that names matching under this policy are equal.
+    pub(in iso) fn normalize(&self, name: &str) -> String {
+        let mut name = name;
+        if self.strip_versions {
+            if let Some(sep) = name.rfind(';') {
+                if name[sep + 1..].bytes().all(|b| b.is_ascii_digit()) {
+                    name = &name[..sep];
+                }
+            }
+            name = name.trim_end_matches('.');
+        }
+        name.chars()
+            .map(|c| match c {
+                _ if self.d_characters => match c.to_ascii_uppercase() {
+                    c @ 'A'..='Z' | c @ '0'..='9' | c @ '_' | c @ '.' => c,
+                    _ => '_',
+                },
+                _ if self.case_insensitive => c.to_lowercase().next().unwrap_or(c),
+                _ => c,
+            })
+            .collect()
+    }
+}
+
+/// Options controlling how an [`IsoFs`] is opened.
+///
+/// Similar to [`std::fs::OpenOptions`].
+///
+/// # Example
+///
+/// ```rust
+/// use opticaldisc::iso::{IsoFs, Options, Tree};
+///
+/// let file = std::fs::File::open("static/iso/alpine.level1.iso").unwrap();
+/// let options = Options::new().tree(Tree::Primary);
+/// let mut iso = IsoFs::with_options(file, options).unwrap();
+/// # assert!(iso.is_dir("/ETC"));
+/// ```
+///
+/// [`IsoFs`]: struct.IsoFs.html
+/// [`std::fs::OpenOptions`]: https://doc.rust-lang.org/std/fs/struct.OpenOptions.html
+#[derive(Clone, Debug)]
+pub struct Options {
+    pub(in iso) tree: Tree,
+    pub(in iso) rock_ridge: bool,
+    pub(in iso) path_table_lookup: bool,
+    pub(in iso) hidden: bool,
+    pub(in iso) lookup: LookupPolicy,
+    pub(in iso) charset: Charset,
+    pub(in iso) toc: Option<Vec<u32>>,
+    pub(in iso) session: Option<usize>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            tree: Tree::default(),
+            rock_ridge: true,
+            path_table_lookup: false,
+            hidden: true,
+            lookup: LookupPolicy::default(),
+            charset: Charset::default(),
+            toc: None,
+            session: None,
+        }
+    }
+}
+
+impl Options {
+    /// Create a new set of options with default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Select the directory hierarchy to browse.
+    ///
+    /// Defaults to [`Tree::Primary`](enum.Tree.html#variant.Primary).
+    pub fn tree(mut self, tree: Tree) -> Self {
+        self.tree = tree;
+        self
+    }
+
+    /// Use the Rock Ridge extensions when they are present.
+    ///
+    /// Rock Ridge names, POSIX attributes, symbolic links, timestamps and
+    /// device numbers are only decoded from the primary and enhanced
+    /// hierarchies. Defaults to `true`.
+    pub fn rock_ridge(mut self, rock_ridge: bool) -> Self {
+        self.rock_ridge = rock_ridge;
+        self
+    }
+
+    /// Resolve directory paths using the path table of the hierarchy.
+    ///
+    /// The extent of the deepest directory of a path is found with a single
+    /// read of the path table, instead of loading the contents of all its
+    /// ancestors. Names are matched under the [`lookup`](#method.lookup)
+    /// policy, like in the directory hierarchy. Since path tables only
+    /// record ISO-9660 or Joliet names, this has no effect when Rock Ridge
+    /// names are in use. Defaults to `false`.
+    pub fn path_table_lookup(mut self, path_table_lookup: bool) -> Self {
+        self.path_table_lookup = path_table_lookup;
+        self
+    }
+
+    /// Include hidden entries when listing the contents of a directory.
+    ///
+    /// Hidden entries can still be accessed using their path. Defaults to
+    /// `true`.
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// Set the policy used to match path components against file names.
+    ///
+    /// Defaults to [`LookupPolicy::exact`](struct.LookupPolicy.html#method.exact).
+    pub fn lookup(mut self, lookup: LookupPolicy) -> Self {
+        self.lookup = lookup;
+        self
+    }
+
+    /// Select the character set used to decode the file identifiers of the
+    /// primary and enhanced hierarchies.
+    ///
+    /// Joliet identifiers are always decoded from UCS-2. Defaults to
+    /// [`Charset::Utf8`](enum.Charset.html#variant.Utf8).
+    pub fn charset(mut self, charset: Charset) -> Self {
+        self.charset = charset;
+        self
+    }
+
+    /// Read the sessions starting at the given sectors, as listed in the
+    /// table of contents of the disc.
+    ///
+    /// By default, the image is scanned for the volume descriptor sets of
+    /// later sessions, starting from the first sector. Sessions are found
+    /// by their Primary Volume Descriptor, or by the Standard File Structure
+    /// Volume Descriptor of a High Sierra volume.
+    pub fn toc(mut self, starts: Vec<u32>) -> Self {
+        self.toc = Some(starts);
+        self
+    }
+
+    /// Read the single session starting at the given sector.
+    ///
+    /// This is a shorthand for [`toc`](#method.toc) with a single session.
+    pub fn session_start(self, start: u32) -> Self {
+        self.toc(vec![start])
+    }
+
+    /// Select the session to browse, by its index in the list of sessions.
+    ///
+    /// Defaults to the last session with a Primary Volume Descriptor, which
+    /// is the one operating systems mount.
+    pub fn session(mut self, session: usize) -> Self {
+        self.session = Some(session);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::LookupPolicy;
+
+    #[test]
+    fn test_normalize() {
+        let exact = LookupPolicy::exact();
+        assert!(exact.is_exact());
+        assert_eq!(exact.normalize("arch;1"), "arch;1");
+
+        let nocase = LookupPolicy::exact().case_insensitive(true);
+        assert_eq!(nocase.normalize("ARCH"), nocase.normalize("arch"));
+        assert_eq!(nocase.normalize("Ärch"), nocase.normalize("ärch"));
+        assert_ne!(nocase.normalize("APK."), nocase.normalize("apk"));
+
+        let strip = LookupPolicy::exact().strip_versions(true);
+        assert_eq!(strip.normalize("APK.;1"), "APK");
+        assert_eq!(strip.normalize("README.TXT;12"), "README.TXT");
+        assert_eq!(strip.normalize("A;B"), "A;B");
+
+        let dchars = LookupPolicy::exact().d_characters(true);
+        assert_eq!(dchars.normalize("my-file.txt"), "MY_FILE.TXT");
+        assert_eq!(dchars.normalize("é"), "_");
+
+        let relaxed = LookupPolicy::relaxed();
+        assert_eq!(relaxed.normalize("x86-64.iso;1"), "X86_64.ISO");
+    }
+}
@@ -0,0 +1,255 @@
+//! Path tables, listing all the directories of a hierarchy.
+//!
+//! Every volume descriptor records the location of a path table in both
+//! little-endian (*L*) and big-endian (*M*) byte order, each possibly
+//! accompanied by an optional copy. The entries of a path table are sorted
+//! by depth and parent, which allows finding the extent of a directory
+//! without reading the extents of all of its ancestors.
+
+mod parser;
+
+pub(in iso) use self::parser::locations;
+
+use std::convert::TryFrom;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use super::options::LookupPolicy;
+use super::record::Format;
+
+/// The byte order of a path table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// The *type L* path table.
+    LittleEndian,
+    /// The *type M* path table.
+    BigEndian,
+}
+
+/// The location of the path tables declared by a volume descriptor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PathTableLocations {
+    pub size: u32,
+    pub l_table: u32,
+    pub optional_l_table: u32,
+    pub m_table: u32,
+    pub optional_m_table: u32,
+}
+
+impl PathTableLocations {
+    /// Get the location of the mandatory path table in the given order.
+    pub fn table(&self, order: ByteOrder) -> u32 {
+        match order {
+            ByteOrder::LittleEndian => self.l_table,
+            ByteOrder::BigEndian => self.m_table,
+        }
+    }
+
+    /// Get the location of the optional path table in the given order,
+    /// if there is one.
+    pub fn optional_table(&self, order: ByteOrder) -> Option<u32> {
+        let location = match order {
+            ByteOrder::LittleEndian => self.optional_l_table,
+            ByteOrder::BigEndian => self.optional_m_table,
+        };
+        if location != 0 {
+            Some(location)
+        } else {
+            None
+        }
+    }
+}
+
+/// An entry of a path table, describing a directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathTableEntry {
+    /// The name of the directory, empty for the root directory.
+    pub name: String,
+    pub ear_length: u8,
+    pub extent: u32,
+    /// The number of the parent directory, starting at 1.
+    pub parent: u16,
+}
+
+/// A path table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathTable {
+    entries: Vec<PathTableEntry>,
+}
+
+impl PathTable {
+    pub(in iso) fn parse(bytes: &[u8], order: ByteOrder, format: Format) -> ::error::Result<Self> {
+        let mut entries = Vec::new();
+        let mut input = bytes;
+        while !input.is_empty() {
+            let (rest, entry) = parser::entry(input, order, format)?;
+            entries.push(entry);
+            input = rest;
+        }
+        Ok(Self { entries })
+    }
+
+    /// Get all the entries of the path table.
+    pub fn entries(&self) -> &[PathTableEntry] {
+        &self.entries
+    }
+
+    /// Get the entry of the directory with the given number, starting at 1.
+    pub fn get(&self, number: u16) -> Option<&PathTableEntry> {
+        (number as usize)
+            .checked_sub(1)
+            .and_then(|index| self.entries.get(index))
+    }
+
+    /// Get the absolute path of the directory with the given number.
+    pub fn path(&self, number: u16) -> Option<PathBuf> {
+        let mut names = Vec::new();
+        let mut current = number;
+        // parents always have a lower number, which prevents loops
+        while current > 1 {
+            let entry = self.get(current)?;
+            if entry.parent >= current {
+                return None;
+            }
+            names.push(entry.name.as_str());
+            current = entry.parent;
+        }
+        Some(names.iter().rev().fold(PathBuf::from("/"), |p, n| p.join(n)))
+    }
+
+    /// Find the number of the directory at the given path.
+    ///
+    /// Paths are resolved from the root directory, and names are compared
+    /// exactly; `None` is returned for paths with `..` components.
+    pub fn find<P: AsRef<Path>>(&self, path: P) -> Option<u16> {
+        self.lookup(path, &LookupPolicy::exact())
+    }
+
+    /// Find the number of the directory at the given path, matching names
+    /// under `policy` when no directory has exactly the requested name.
+    ///
+    /// `None` is also returned when several directories match a name, or
+    /// for names with an explicit version, so that they are resolved by
+    /// the directory hierarchy.
+    pub(in iso) fn lookup<P: AsRef<Path>>(&self, path: P, policy: &LookupPolicy) -> Option<u16> {
+        let mut current = 1;
+        for component in path.as_ref().components() {
+            current = match component {
+                Component::RootDir | Component::CurDir => current,
+                Component::Normal(name) => {
+                    let name = name.to_str()?;
+                    let parent = current;
+                    let children = self
+                        .entries
+                        .iter()
+                        .enumerate()
+                        .skip(current as usize)
+                        .filter(|&(_, e)| e.parent == parent);
+                    let index = match children.clone().find(|&(_, e)| e.name == name) {
+                        Some((index, _)) => index,
+                        None if policy.is_exact() || name.contains(';') => return None,
+                        None => {
+                            let key = policy.normalize(name);
+                            let mut matches = children.filter(|&(_, e)| policy.normalize(&e.name) == key);
+                            let (index, _) = matches.next()?;
+                            if matches.next().is_some() {
+                                return None;
+                            }
+                            index
+                        }
+                    };
+                    // directories past the 65535th cannot be numbered
+                    u16::try_from(index + 1).ok()?
+                }
+                Component::ParentDir | Component::Prefix(_) => return None,
+            }
+        }
+        self.get(current).map(|_| current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::path::PathBuf;
+
+    use utils::charsets::Charset;
+
+    use super::super::options::LookupPolicy;
+    use super::super::record::Format;
+    use super::ByteOrder;
+    use super::PathTable;
+
+    /// The type L path table of `static/iso/alpine.level1.iso`, truncated.
+    fn table() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for &(name, extent, parent) in [
+            (&b"\0"[..], 20u32, 1u16),
+            (&b"ETC"[..], 22, 1),
+            (&b"LIB"[..], 40, 1),
+            (&b"APK"[..], 23, 2),
+        ].iter()
+        {
+            bytes.push(name.len() as u8);
+            bytes.push(0);
+            bytes.extend_from_slice(&extent.to_le_bytes());
+            bytes.extend_from_slice(&parent.to_le_bytes());
+            bytes.extend_from_slice(name);
+            if !name.len().is_multiple_of(2) {
+                bytes.push(0);
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_parse() {
+        let pt = PathTable::parse(&table(), ByteOrder::LittleEndian, Format::Iso(Charset::Auto)).unwrap();
+        assert_eq!(pt.entries().len(), 4);
+        assert_eq!(pt.get(1).unwrap().name, "");
+        assert_eq!(pt.get(4).unwrap().extent, 23);
+        assert_eq!(pt.path(4), Some(PathBuf::from("/ETC/APK")));
+        assert_eq!(pt.path(1), Some(PathBuf::from("/")));
+    }
+
+    #[test]
+    fn test_find() {
+        let pt = PathTable::parse(&table(), ByteOrder::LittleEndian, Format::Iso(Charset::Auto)).unwrap();
+        assert_eq!(pt.find("/"), Some(1));
+        assert_eq!(pt.find("/ETC/APK"), Some(4));
+        assert_eq!(pt.find("LIB"), Some(3));
+        assert_eq!(pt.find("/LIB/APK"), None);
+        assert_eq!(pt.find("/ETC/../LIB"), None);
+    }
+
+    #[test]
+    fn test_lookup() {
+        let mut bytes = table();
+        bytes.extend_from_slice(b"\x03\x00\x2a\x00\x00\x00\x01\x00A_B\x00");
+        bytes.extend_from_slice(b"\x03\x00\x2b\x00\x00\x00\x01\x00A-B\x00");
+        let pt = PathTable::parse(&bytes, ByteOrder::LittleEndian, Format::Iso(Charset::Auto)).unwrap();
+
+        let relaxed = LookupPolicy::relaxed();
+        assert_eq!(pt.find("/etc/apk"), None);
+        assert_eq!(pt.lookup("/etc/apk", &relaxed), Some(4));
+        assert_eq!(pt.lookup("/etc/apk;1", &relaxed), None);
+
+        // an exact match is never ambiguous, other ones are left to the tree
+        assert_eq!(pt.lookup("/A-B", &relaxed), Some(6));
+        assert_eq!(pt.lookup("/a-b", &relaxed), None);
+    }
+
+    #[test]
+    fn test_find_overflow() {
+        // the 65537th directory would wrap around to the root directory
+        let mut bytes = table();
+        for _ in 4..65536 {
+            bytes.extend_from_slice(b"\x01\x00\x2a\x00\x00\x00\x01\x00A\x00");
+        }
+        bytes.extend_from_slice(b"\x01\x00\x2a\x00\x00\x00\x01\x00Z\x00");
+        let pt = PathTable::parse(&bytes, ByteOrder::LittleEndian, Format::Iso(Charset::Auto)).unwrap();
+        assert_eq!(pt.entries().len(), 65537);
+        assert_eq!(pt.find("/Z"), None);
+    }
+}
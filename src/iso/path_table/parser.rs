@@ -0,0 +1,139 @@
+use nom::be_u32;
+use nom::be_u8;
+use nom::le_u32;
+use nom::Endianness;
+
+use super::super::record::parser::identifier;
+use super::super::record::Format;
+use super::ByteOrder;
+use super::PathTableEntry;
+use super::PathTableLocations;
+
+#[rustfmt::skip]
+named_args!(pub locations(size: u32)<PathTableLocations>,
+    do_parse!(
+        l_table:          le_u32                             >>
+        optional_l_table: le_u32                             >>
+        m_table:          be_u32                             >>
+        optional_m_table: be_u32                             >>
+                          (PathTableLocations {
+                              size,
+                              l_table,
+                              optional_l_table,
+                              m_table,
+                              optional_m_table,
+                          })
+    )
+);
+
+/// Parse a path table entry, whose layout depends on whether the volume is
+/// a High Sierra volume.
+pub fn entry(input: &[u8], order: ByteOrder, format: Format) -> ::nom::IResult<&[u8], PathTableEntry> {
+    if format.is_high_sierra() {
+        hs_entry(input, order, format)
+    } else {
+        iso_entry(input, order, format)
+    }
+}
+
+#[rustfmt::skip]
+named_args!(hs_entry(order: ByteOrder, format: Format)<PathTableEntry>,
+    do_parse!(
+        extent:     u32!(endianness(order))                  >>
+        ear_length: be_u8                                    >>
+        length:     be_u8                                    >>
+        parent:     u16!(endianness(order))                  >>
+        name:       map_opt!(take!(length), |id| name(id, format)) >>
+                    take!(length % 2)                        >>
+                    (PathTableEntry {
+                        name,
+                        ear_length,
+                        extent,
+                        parent,
+                    })
+    )
+);
+
+#[rustfmt::skip]
+named_args!(iso_entry(order: ByteOrder, format: Format)<PathTableEntry>,
+    do_parse!(
+        length:     be_u8                                    >>
+        ear_length: be_u8                                    >>
+        extent:     u32!(endianness(order))                  >>
+        parent:     u16!(endianness(order))                  >>
+        name:       map_opt!(take!(length), |id| name(id, format)) >>
+                    take!(length % 2)                        >>
+                    (PathTableEntry {
+                        name,
+                        ear_length,
+                        extent,
+                        parent,
+                    })
+    )
+);
+
+/// Get the `nom` endianness of a byte order.
+fn endianness(order: ByteOrder) -> Endianness {
+    match order {
+        ByteOrder::LittleEndian => Endianness::Little,
+        ByteOrder::BigEndian => Endianness::Big,
+    }
+}
+
+/// Decode the name of a directory, the root having an empty name.
+fn name(id: &[u8], format: Format) -> Option<String> {
+    match id {
+        b"\0" => Some(String::new()),
+        _ => identifier(id, format),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use utils::charsets::Charset;
+
+    use super::super::super::record::Format;
+    use super::super::ByteOrder;
+
+    #[test]
+    fn test_entry() {
+        let l = b"\x03\x00\x17\x00\x00\x00\x02\x00APK\x00";
+        let (rest, entry) = super::entry(l, ByteOrder::LittleEndian, Format::Iso(Charset::Auto)).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(entry.name, "APK");
+        assert_eq!(entry.extent, 0x17);
+        assert_eq!(entry.parent, 2);
+
+        let m = b"\x03\x00\x00\x00\x00\x17\x00\x02APK\x00";
+        let (_, m_entry) = super::entry(m, ByteOrder::BigEndian, Format::Iso(Charset::Auto)).unwrap();
+        assert_eq!(entry, m_entry);
+    }
+
+    #[test]
+    fn test_entry_high_sierra() {
+        let l = b"\x17\x00\x00\x00\x00\x03\x02\x00APK\x00";
+        let format = Format::HighSierra(Charset::Auto);
+        let (rest, entry) = super::entry(l, ByteOrder::LittleEndian, format).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(entry.name, "APK");
+        assert_eq!(entry.extent, 0x17);
+        assert_eq!(entry.parent, 2);
+    }
+
+    #[test]
+    fn test_entry_joliet() {
+        let l = b"\x06\x00\x17\x00\x00\x00\x02\x00\x00a\x00p\x00k";
+        let (_, entry) = super::entry(l, ByteOrder::LittleEndian, Format::Joliet).unwrap();
+        assert_eq!(entry.name, "apk");
+    }
+
+    #[test]
+    fn test_locations() {
+        let raw = b"\x13\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x15\x00\x00\x00\x00";
+        let (_, locations) = super::locations(raw, 10).unwrap();
+        assert_eq!(locations.l_table, 0x13);
+        assert_eq!(locations.m_table, 0x15);
+        assert_eq!(locations.optional_table(ByteOrder::BigEndian), None);
+    }
+}
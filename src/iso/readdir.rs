@@ -0,0 +1,306 @@
+use std::collections::VecDeque;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::ops::Deref;
+use std::rc::Rc;
+
+use super::super::error::Result;
+
+use super::constants::SECTOR_SIZE;
+use super::metadata::Metadata;
+use super::node::Node;
+use super::record::parser::record;
+use super::record::Record;
+use super::record::Section;
+use super::rockridge::RockRidge;
+use super::susp;
+
+/// A reader of the children of a directory node, in on-disc order.
+///
+/// Directory records are parsed one sector at a time, and assembled into
+/// child nodes: Rock Ridge entries are decoded, sections of multi-extent
+/// files are merged, relocated directories are grafted at their logical
+/// location, and associated files are attached to their primary file.
+pub(in iso) struct Records<'a, N, H: 'a>
+where
+    N: Deref<Target = Node>,
+    H: Read + Seek,
+{
+    parent: N,
+    handle: &'a mut H,
+    buffer: [u8; SECTOR_SIZE as usize],
+    offset: usize,
+    total: u64,
+    sections: Option<Record>,
+    merged: VecDeque<Record>,
+    associated: Vec<Node>,
+    ready: VecDeque<Node>,
+    finished: bool,
+}
+
+impl<'a, N, H: 'a> Records<'a, N, H>
+where
+    N: Deref<Target = Node>,
+    H: Read + Seek,
+{
+    /// Create a reader of the children of `parent`, which must be a
+    /// directory.
+    pub(in iso) fn new(parent: N, handle: &'a mut H) -> Self {
+        Self {
+            parent,
+            handle,
+            buffer: [0; SECTOR_SIZE as usize],
+            offset: SECTOR_SIZE as usize,
+            total: 0,
+            sections: None,
+            merged: VecDeque::new(),
+            associated: Vec::new(),
+            ready: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    /// Parse the next directory record, reading the next sector if needed.
+    fn next_record(&mut self) -> Result<Option<Record>> {
+        let start = (self.parent.record.extent as u64 + self.parent.record.ear_length as u64) * SECTOR_SIZE;
+
+        // a sector with directory records starts with the length of the first
+        // directory record, which can never have a length of 0
+        while self.offset >= self.buffer.len() || self.buffer[self.offset] == 0 {
+            if self.total >= self.parent.record.data_length as u64 {
+                return Ok(None);
+            }
+            // seek explicitly since continuation areas may be read in between
+            self.handle.seek(SeekFrom::Start(start + self.total))?;
+            self.handle.read_exact(&mut self.buffer)?;
+            self.total += SECTOR_SIZE;
+            self.offset = 0;
+        }
+
+        // parse the next record and advance the buffer cursor
+        let (rem, mut record) = record(&self.buffer[self.offset..], self.parent.format)?;
+        self.offset = SECTOR_SIZE as usize - rem.len();
+
+        // decode the Rock Ridge entries if the SUSP is in use
+        if let (true, Some(skip)) = (self.parent.rock_ridge, self.parent.susp) {
+            let entries = susp::read(&record.system_use, skip, self.handle)?;
+            record.rock_ridge = RockRidge::from_entries(&entries);
+        }
+
+        Ok(Some(record))
+    }
+
+    /// Check whether the directory described by `record` holds relocated
+    /// directories, i.e. records with a Rock Ridge `RE` entry.
+    fn holds_relocations(&mut self, record: &Record) -> Result<bool> {
+        let directory = self.parent.create_child(record.clone());
+        let mut records = Records::new(&directory, &mut *self.handle);
+        while let Some(record) = records.next_record()? {
+            if record.rock_ridge.is_some_and(|rr| rr.relocated) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Merge the sections of a multi-extent file into the record of its
+    /// first section, which may span several sectors.
+    ///
+    /// Records are queued once complete. A group of sections interrupted by
+    /// another record, or by the end of the directory, is queued with the
+    /// sections read so far.
+    fn merge(&mut self, mut record: Record) {
+        match self.sections.take() {
+            Some(mut first)
+                if first.name == record.name
+                    && first.version == record.version
+                    && first.is_associated == record.is_associated =>
+            {
+                first.sections.push(Section::from(&record));
+                if record.multi_extent {
+                    self.sections = Some(first);
+                } else {
+                    first.multi_extent = false;
+                    self.merged.push_back(first);
+                }
+            }
+            pending => {
+                self.merged.extend(pending);
+                if record.multi_extent {
+                    record.sections = vec![Section::from(&record)];
+                    self.sections = Some(record);
+                } else {
+                    self.merged.push_back(record);
+                }
+            }
+        }
+    }
+
+    /// Stop reading records, queuing the sections of a pending multi-extent
+    /// file.
+    fn finish(&mut self) {
+        self.finished = true;
+        self.merged.extend(self.sections.take());
+    }
+
+    /// Turn a record into a child node, and queue it unless the record does
+    /// not stand for a child on its own.
+    fn child(&mut self, record: Record) -> Result<()> {
+        // skip the `.` and `..` entries, and the relocated directories
+        if record.name == "\0" || record.name == "\x01" {
+            return Ok(());
+        }
+        let record = match record.rock_ridge {
+            Some(ref rr) if rr.relocated => return Ok(()),
+            Some(RockRidge { child_link: Some(location), .. }) => {
+                self.parent.relocated_record(record, location, self.handle)?
+            }
+            _ => record,
+        };
+        if self.parent.is_relocation_directory(&record) && self.holds_relocations(&record)? {
+            return Ok(());
+        }
+
+        // associated files are recorded right before the file they are
+        // associated with, and attached to it: the others are kept as
+        // regular files, in on-disc order
+        let mut child = self.parent.create_child(record);
+        let name = child.name().to_owned();
+        if self.associated.iter().any(|a| a.name() != name) {
+            self.ready.extend(self.associated.drain(..));
+        }
+        if child.record.is_associated {
+            self.associated.push(child);
+        } else {
+            child.associated = self.associated.pop().map(Rc::new);
+            self.ready.extend(self.associated.drain(..));
+            self.ready.push_back(child);
+        }
+        Ok(())
+    }
+}
+
+impl<'a, N, H: 'a> Iterator for Records<'a, N, H>
+where
+    N: Deref<Target = Node>,
+    H: Read + Seek,
+{
+    type Item = Result<Node>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(child) = self.ready.pop_front() {
+                return Some(Ok(child));
+            }
+            let result = match self.merged.pop_front() {
+                Some(record) => self.child(record),
+                // keep the associated files without a matching file as
+                // regular files
+                None if self.finished && !self.associated.is_empty() => {
+                    self.ready.extend(self.associated.drain(..));
+                    Ok(())
+                }
+                None if self.finished => return None,
+                None => match self.next_record() {
+                    // a `.` entry of another directory marks the end of the records
+                    Ok(Some(ref record)) if record.name == "\0" && record.extent != self.parent.record.extent => {
+                        self.finish();
+                        Ok(())
+                    }
+                    Ok(Some(record)) => {
+                        self.merge(record);
+                        Ok(())
+                    }
+                    Ok(None) => {
+                        self.finish();
+                        Ok(())
+                    }
+                    Err(err) => Err(err),
+                },
+            };
+            if let Err(err) = result {
+                self.finished = true;
+                self.sections = None;
+                self.merged.clear();
+                self.associated.clear();
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+/// Iterator over the entries of a directory, in on-disc order.
+///
+/// Directory records are read from the disc as the iterator advances,
+/// without being added to the cache of the [`IsoFs`]. Consecutive records
+/// with the same name are yielded once, with their highest version, like
+/// in [`IsoFs::read_dir`] when the records are sorted as mandated by
+/// ISO-9660.
+///
+/// [`IsoFs`]: struct.IsoFs.html
+/// [`IsoFs::read_dir`]: struct.IsoFs.html#method.read_dir
+pub struct ReadDir<'a, H: 'a>
+where
+    H: Read + Seek,
+{
+    records: Records<'a, Rc<Node>, H>,
+    pending: Option<Node>,
+    hidden: bool,
+}
+
+impl<'a, H: 'a> ReadDir<'a, H>
+where
+    H: Read + Seek,
+{
+    pub(in iso) fn new(directory: Rc<Node>, handle: &'a mut H, hidden: bool) -> Self {
+        Self {
+            records: Records::new(directory, handle),
+            pending: None,
+            hidden,
+        }
+    }
+}
+
+impl<'a, H: 'a> Iterator for ReadDir<'a, H>
+where
+    H: Read + Seek,
+{
+    type Item = Result<Metadata>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let next = match self.records.next() {
+                Some(Ok(child)) => Some(child),
+                Some(Err(err)) => return Some(Err(err)),
+                None => None,
+            };
+            let finished = next.is_none();
+
+            // hold each child back until a record with another name is read,
+            // keeping the highest version of the name
+            let entry = match (self.pending.take(), next) {
+                (Some(pending), Some(child)) if pending.name() == child.name() => {
+                    if child.record.version > pending.record.version {
+                        self.pending = Some(child);
+                    } else {
+                        self.pending = Some(pending);
+                    }
+                    None
+                }
+                (pending, child) => {
+                    self.pending = child;
+                    pending
+                }
+            };
+
+            match entry {
+                Some(entry) if self.hidden || !entry.record.is_hidden => {
+                    return Some(Ok(Metadata::from(Rc::new(entry))))
+                }
+                _ if finished => return None,
+                _ => (),
+            }
+        }
+    }
+}
@@ -3,21 +3,111 @@ pub mod parser;
 use chrono::DateTime;
 use chrono::offset::FixedOffset;
 
+use utils::charsets::Charset;
+
+use super::rockridge::RockRidge;
+
+/// The layout of the directory hierarchy a record was found in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Identifiers made of ISO-9660 d-characters (the primary hierarchy),
+    /// decoded using the given charset.
+    Iso(Charset),
+    /// Identifiers encoded in UCS-2 big-endian (a Joliet hierarchy).
+    Joliet,
+    /// Relaxed identifiers without version numbers (the hierarchy of an
+    /// Enhanced Volume Descriptor), decoded using the given charset.
+    Enhanced(Charset),
+    /// Identifiers made of d-characters in a High Sierra volume, whose
+    /// directory records store dates without a timezone.
+    HighSierra(Charset),
+}
+
+impl Format {
+    /// Check whether the format is the one of a High Sierra volume.
+    pub fn is_high_sierra(&self) -> bool {
+        matches!(*self, Format::HighSierra(_))
+    }
+}
+
+/// A section of a file, i.e. a single extent of a multi-extent file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Section {
+    pub extent: u32,
+    pub ear_length: u8,
+    pub data_length: u32,
+    pub unit_size: u8,
+    pub gap_size: u8,
+}
+
+impl<'a> From<&'a Record> for Section {
+    fn from(record: &'a Record) -> Self {
+        Self {
+            extent: record.extent,
+            ear_length: record.ear_length,
+            data_length: record.data_length,
+            unit_size: record.unit_size,
+            gap_size: record.gap_size,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Record {
-    pub date: DateTime<FixedOffset>,
+    /// The recording date of the record, if it is set and valid.
+    pub date: Option<DateTime<FixedOffset>>,
+    /// The recording date as recorded, to tell unset dates from invalid
+    /// ones.
+    pub raw_date: Vec<u8>,
     pub name: String,
+    /// The file identifier as recorded, including its version number.
+    pub identifier: Vec<u8>,
     pub extent: u32,
     pub ear_length: u8,
     pub data_length: u32,
+    /// The size of the file units of an interleaved file, in blocks.
+    pub unit_size: u8,
+    /// The size of the gaps between the file units, in blocks.
+    pub gap_size: u8,
     pub seq_number: u16,
     pub version: Option<u8>,
     pub is_dir: bool,
     pub is_hidden: bool,
+    /// Whether the record describes the associated file of another file.
+    pub is_associated: bool,
+    /// Whether the structure of the file is described by the record format
+    /// of its Extended Attribute Record.
+    pub has_record_format: bool,
+    /// Whether owner and permissions are set in the Extended Attribute
+    /// Record of the file.
+    pub is_protected: bool,
+    /// Whether the record is not the final section of a file.
+    pub multi_extent: bool,
+    /// All the sections of a multi-extent file, once merged.
+    pub sections: Vec<Section>,
+    pub system_use: Vec<u8>,
+    pub rock_ridge: Option<RockRidge>,
 }
 
 impl Record {
-    pub fn parse(input: &[u8]) -> ::error::Result<Self> {
-        Ok(parser::record(input)?.1)
+    pub fn parse(input: &[u8], format: Format) -> ::error::Result<Self> {
+        Ok(parser::record(input, format)?.1)
+    }
+
+    /// Get the sections of the file, in order.
+    pub fn sections(&self) -> Vec<Section> {
+        if self.sections.is_empty() {
+            vec![Section::from(self)]
+        } else {
+            self.sections.clone()
+        }
+    }
+
+    /// Get the total length of the file, in bytes.
+    pub fn length(&self) -> u64 {
+        self.sections()
+            .iter()
+            .map(|section| section.data_length as u64)
+            .sum()
     }
 }
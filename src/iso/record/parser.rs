@@ -1,63 +1,106 @@
 use btoi::btou;
 
 use chrono::DateTime;
+use chrono::NaiveDate;
+use chrono::NaiveDateTime;
 use chrono::TimeZone;
 use chrono::offset::FixedOffset;
 
-use memchr::memrchr;
-
 use nom::be_u8;
 use nom::Context;
 use nom::Err::Failure;
 
 use utils::parsers::both_u16;
+use utils::charsets::ucs2_be;
 use utils::parsers::both_u32;
 
+use super::Format;
 use super::Record;
 
-#[cfg_attr(rustfmt, rustfmt_skip)]
-named!(datetime(&[u8]) -> DateTime<FixedOffset>,
-    do_parse!(
-        year:  be_u8 >>
-        month: matching!(1...12) >>
-        day:   matching!(1...31) >>
-        hour:  matching!(0...23) >>
-        min:   matching!(0...59) >>
-        sec:   matching!(0...59) >>
-        tz:    matching!(0...100) >>
-               (FixedOffset::east((tz as i32 - 48) * 900)
-                    .ymd(year as i32 + 1900, month as u32, day as u32)
-                    .and_hms(hour as u32, min as u32, sec as u32)
-               )
-    )
+/// Attach an offset from GMT, in 15 minutes intervals, to a local date.
+///
+/// Returns `None` if the offset is outside of the `-48..=52` range.
+pub fn with_offset(local: NaiveDateTime, offset: i8) -> Option<DateTime<FixedOffset>> {
+    if !(-48..=52).contains(&offset) {
+        return None;
+    }
+    FixedOffset::east_opt(offset as i32 * 900)?
+        .from_local_datetime(&local)
+        .single()
+}
+
+/// Decode a 7-byte date, if it is set and valid.
+///
+/// High Sierra dates are only 6 bytes long, and have no timezone: they are
+/// decoded as GMT.
+fn short_datetime(buff: &[u8]) -> Option<DateTime<FixedOffset>> {
+    let date = NaiveDate::from_ymd_opt(buff[0] as i32 + 1900, buff[1] as u32, buff[2] as u32)?;
+    let local = date.and_hms_opt(buff[3] as u32, buff[4] as u32, buff[5] as u32)?;
+    with_offset(local, buff.get(6).map(|&tz| tz as i8).unwrap_or(0))
+}
+
+#[rustfmt::skip]
+named!(pub datetime(&[u8]) -> DateTime<FixedOffset>,
+    map_opt!(take!(7), short_datetime)
 );
 
-#[cfg_attr(rustfmt, rustfmt_skip)]
-pub fn versioned_name(input: &[u8], is_dir: bool) -> ::nom::IResult<&[u8], (&str, Option<u8>)> {
-    let version: Option<u8>;
-    let name: &[u8];
+// Parse a 7-byte date, giving `None` if it is unset (all zeroes) or invalid.
+#[rustfmt::skip]
+named!(pub optional_datetime(&[u8]) -> Option<DateTime<FixedOffset>>,
+    map!(take!(7), short_datetime)
+);
+
+/// Decode an identifier recorded in the given format.
+///
+/// The `.` and `..` identifiers are single bytes whatever the hierarchy.
+pub fn identifier(buff: &[u8], format: Format) -> Option<String> {
+    match format {
+        _ if buff.len() == 1 && buff[0] <= 1 => Some(String::from(buff[0] as char)),
+        Format::Iso(charset) | Format::Enhanced(charset) | Format::HighSierra(charset) => {
+            Some(charset.decode(buff))
+        }
+        Format::Joliet => ucs2_be(buff),
+    }
+}
+
+#[rustfmt::skip]
+pub fn versioned_name(input: &[u8], is_dir: bool, format: Format) -> ::nom::IResult<&[u8], (String, Option<u8>)> {
     let (i1, size) = try_parse!(input, be_u8);
     let (i2, buff) = try_parse!(i1, take!(size));
-    let len = size as usize;
-
-    if let Some(sep) = memrchr(b';', buff) {
-        name = if buff[sep - 1] == b'.' { &buff[..sep - 1] } else { &buff[..sep] };
-        version = match btou(&buff[sep+1..]) {
-            Ok(version_num) => Some(version_num),
-            Err(_) => return Err(Failure(Context::Code(&buff[sep+1..], ::nom::ErrorKind::MapRes))),
-        };
-    } else {
-        name = &buff[..];
-        version = None
+
+    let full = match identifier(buff, format) {
+        Some(full) => full,
+        None => return Err(Failure(Context::Code(buff, ::nom::ErrorKind::MapRes))),
+    };
+
+    // enhanced identifiers have no version number, and a separator not
+    // followed by a version number is part of the name
+    if let Format::Enhanced(_) = format {
+        return Ok((i2, (full, None)));
+    }
+    let split = full.rfind(';').and_then(|sep| {
+        btou(&full.as_bytes()[sep+1..]).ok().map(|version: u8| (sep, version))
+    });
+    match split {
+        Some((sep, version)) => {
+            let name = &full[..sep];
+            let name = name.strip_suffix('.').unwrap_or(name);
+            Ok((i2, (name.to_owned(), Some(version))))
+        }
+        None => Ok((i2, (full, None))),
     }
+}
 
-    match ::std::str::from_utf8(name) {
-        Ok(name_str) => Ok((i2, (name_str, version))),
-        Err(_) => Err(Failure(Context::Code(&buff[len-1..], ::nom::ErrorKind::MapRes))),
+/// Get the length of the date of a record in the given format.
+fn date_length(format: Format) -> usize {
+    if format.is_high_sierra() {
+        6
+    } else {
+        7
     }
 }
 
-#[cfg_attr(rustfmt, rustfmt_skip)]
+#[rustfmt::skip]
 named!(record_flags(&[u8]) -> (bool, bool, bool, bool, bool, bool),
     bits!(
         do_parse!(
@@ -80,35 +123,59 @@ named!(record_flags(&[u8]) -> (bool, bool, bool, bool, bool, bool),
     )
 );
 
-#[cfg_attr(rustfmt, rustfmt_skip)]
-named!(pub record(&[u8]) -> Record,
-    do_parse!(
-                        not!(peek!(tag!("\0")))         >>
-        length:         be_u8                           >>
-        ear_length:     be_u8                           >>
-        extent:         both_u32                        >>
-        data_length:    both_u32                        >>
-        date:           datetime                        >>
-        flags:          record_flags                    >>
-        unit_size:      be_u8                           >>
-        gap_size:       be_u8                           >>
-        seq_number:     both_u16                        >>
-        id_length:      peek!(be_u8)                    >>
-        versioned_id:   apply!(versioned_name, flags.1) >>
-                        take!(length - id_length - 33)  >>
+/// Get the length of the padding field following a file identifier.
+fn padding_length(id_length: u8) -> usize {
+    usize::from(id_length.is_multiple_of(2))
+}
+
+/// Get the length of the system use area of a record.
+fn system_use_length(length: u8, id_length: u8) -> usize {
+    (length as usize).saturating_sub(33 + id_length as usize + padding_length(id_length))
+}
+
+#[rustfmt::skip]
+pub fn record(input: &[u8], format: Format) -> ::nom::IResult<&[u8], Record> {
+    do_parse!(input,
+                        not!(peek!(tag!("\0")))                 >>
+        length:         be_u8                                   >>
+        ear_length:     be_u8                                   >>
+        extent:         both_u32                                >>
+        data_length:    both_u32                                >>
+        raw_date:       take!(date_length(format))              >>
+        flags:          record_flags                            >>
+                        cond!(format.is_high_sierra(), take!(1)) >>
+        unit_size:      be_u8                                   >>
+        gap_size:       be_u8                                   >>
+        seq_number:     both_u16                                >>
+        id_length:      peek!(be_u8)                            >>
+        raw_id:         peek!(preceded!(be_u8, take!(id_length)))  >>
+        versioned_id:   apply!(versioned_name, flags.1, format) >>
+                        take!(padding_length(id_length))        >>
+        system_use:     take!(system_use_length(length, id_length)) >>
                         (Record {
-                            name: versioned_id.0.to_owned(),
+                            name: versioned_id.0,
+                            identifier: raw_id.to_vec(),
                             version: versioned_id.1,
-                            date,
+                            date: short_datetime(raw_date),
+                            raw_date: raw_date.to_vec(),
                             extent,
                             ear_length,
                             data_length,
+                            unit_size,
+                            gap_size,
                             seq_number,
                             is_hidden: flags.0,
-                            is_dir: flags.1
+                            is_associated: flags.2,
+                            has_record_format: flags.3,
+                            is_protected: flags.4,
+                            is_dir: flags.1,
+                            multi_extent: flags.5,
+                            sections: Vec::new(),
+                            system_use: system_use.to_vec(),
+                            rock_ridge: None,
                         })
     )
-);
+}
 
 #[cfg(test)]
 mod tests {
@@ -116,28 +183,99 @@ mod tests {
     use chrono::Datelike;
     use chrono::Timelike;
 
+    use utils::charsets::Charset;
+
+    use super::Format;
+
     #[test]
     fn test_filename() {
         let buf = b"\x06APK.;1";
-        let (_, (name, v)) = super::versioned_name(buf, false).unwrap();
+        let (_, (name, v)) = super::versioned_name(buf, false, Format::Iso(Charset::Auto)).unwrap();
         assert_eq!(v, Some(1));
         assert_eq!(name, "APK");
     }
 
+    #[test]
+    fn test_filename_charset() {
+        let buf = b"\x07CAF\xc9.;1";
+        let (_, (name, v)) = super::versioned_name(buf, false, Format::Iso(Charset::Latin1)).unwrap();
+        assert_eq!((name.as_str(), v), ("CAFÉ", Some(1)));
+        let (_, (name, _)) = super::versioned_name(buf, false, Format::Iso(Charset::Utf8)).unwrap();
+        assert_eq!(name, "CAF\u{FFFD}");
+
+        let buf2 = b"\x05A;B;C";
+        let (_, (name, v)) = super::versioned_name(buf2, false, Format::Iso(Charset::Auto)).unwrap();
+        assert_eq!((name.as_str(), v), ("A;B;C", None));
+    }
+
+    #[test]
+    fn test_filename_enhanced() {
+        let buf = b"\x0Clong name.;1";
+        let format = Format::Enhanced(Charset::Auto);
+        let (_, (name, v)) = super::versioned_name(buf, false, format).unwrap();
+        assert_eq!((name.as_str(), v), ("long name.;1", None));
+    }
+
+    #[test]
+    fn test_filename_joliet() {
+        let buf = b"\x16\0R\0E\0A\0D\0M\0E\0.\0m\0d\0;\x001";
+        let (_, (name, v)) = super::versioned_name(buf, false, Format::Joliet).unwrap();
+        assert_eq!(v, Some(1));
+        assert_eq!(name, "README.md");
+
+        let buf2 = b"\x01\x01";
+        let (_, (name, v)) = super::versioned_name(buf2, true, Format::Joliet).unwrap();
+        assert_eq!(v, None);
+        assert_eq!(name, "\x01");
+
+        let buf3 = b"\x03\0A\0";
+        assert!(super::versioned_name(buf3, false, Format::Joliet).is_err());
+    }
+
     #[test]
     fn test_record() {
         let buf = b"\"\0\x13\0\0\0\0\0\0\x13\0\x08\0\0\0\0\x08\0v\x04\x01\x05\x05\x17\0\x02\0\0\x01\0\0\x01\x01\0";
-        let (_, dr) = super::record(buf).unwrap();
+        let (_, dr) = super::record(buf, Format::Iso(Charset::Auto)).unwrap();
 
         let buf2 = b"`\x00\x13\x00\x00\x00\x00\x00\x00\x13\x00\x08\x00\x00\x00\x00\x08\x00v\x04\x01\x05\x05\x17\x00\x02\x00\x00\x01\x00\x00\x01\x01\x01PX$\x01\xedA\x00\x00\x00\x00A\xed\x01\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00TF\x1a\x01\x0ev\x04\x01\x05\x05\x17\x00v\x04\x01\x05\x05\x18\x00v\x04\x01\x05\x05\x17\x00";
-        let (_, dr) = super::record(&buf2[..]).unwrap();
+        let (_, dr) = super::record(&buf2[..], Format::Iso(Charset::Auto)).unwrap();
+        assert_eq!(dr.system_use.len(), 62);
+        assert_eq!(&dr.system_use[..2], b"PX");
 
         let buf3 = b"\x28\x00\xf6\x00\x00\x00\x00\x00\x00\xf6\x88\x16\x03\x00\x00\x03\x16\x88v\x05\x01\x0f3\x1e\x08\x00\x00\x00\x01\x00\x00\x01\x06APK.;1\x00";
-        let (_, dr) = super::record(&buf3[..]).unwrap();
+        let (_, dr) = super::record(&buf3[..], Format::Iso(Charset::Auto)).unwrap();
         assert_eq!(dr.name, "APK");
+        assert_eq!(dr.identifier, b"APK.;1");
 
         let buf4 = b"\0\0\0\0\0";
-        assert!(super::record(&buf4[..]).is_err());
+        assert!(super::record(&buf4[..], Format::Iso(Charset::Auto)).is_err());
+    }
+
+    #[test]
+    fn test_record_high_sierra() {
+        let buf = b"\x28\x00\xf6\x00\x00\x00\x00\x00\x00\xf6\x88\x16\x03\x00\x00\x03\x16\x88V\x05\x01\x0f3\x1e\x02\x00\x00\x00\x01\x00\x00\x01\x06APK.;1\x00";
+        let (_, dr) = super::record(&buf[..], Format::HighSierra(Charset::Auto)).unwrap();
+        assert_eq!(dr.name, "APK");
+        assert!(dr.is_dir);
+        assert_eq!(dr.data_length, 0x31688);
+        let date = dr.date.unwrap();
+        assert_eq!((date.year(), date.month(), date.day()), (1986, 5, 1));
+        assert_eq!(date.offset().local_minus_utc(), 0);
+        assert_eq!(dr.raw_date, b"V\x05\x01\x0f3\x1e");
+    }
+
+    #[test]
+    fn test_record_date() {
+        // unset and invalid dates are both `None`, but keep their raw bytes
+        let mut buf = b"\x28\x00\xf6\x00\x00\x00\x00\x00\x00\xf6\x88\x16\x03\x00\x00\x03\x16\x88\0\0\0\0\0\0\0\x00\x00\x00\x01\x00\x00\x01\x06APK.;1\x00".to_vec();
+        let (_, dr) = super::record(&buf, Format::Iso(Charset::Auto)).unwrap();
+        assert_eq!(dr.date, None);
+        assert_eq!(dr.raw_date, b"\0\0\0\0\0\0\0");
+
+        buf[18..25].copy_from_slice(b"\x76\x0D\x20\x09\x23\x2D\x01");
+        let (_, dr) = super::record(&buf, Format::Iso(Charset::Auto)).unwrap();
+        assert_eq!(dr.date, None);
+        assert_eq!(dr.raw_date, b"\x76\x0D\x20\x09\x23\x2D\x01");
     }
 
     #[test]
@@ -150,6 +288,26 @@ mod tests {
         assert_eq!(dt.hour(), 9);
         assert_eq!(dt.minute(), 35);
         assert_eq!(dt.second(), 45);
+        assert_eq!(dt.offset().local_minus_utc(), 15 * 60);
+    }
+
+    #[test]
+    fn test_datetime_offsets() {
+        let (_, dt) = super::datetime(b"\x76\x0B\x0D\x09\x23\x2D\xD0").unwrap();
+        assert_eq!(dt.offset().local_minus_utc(), -12 * 3600);
+        let (_, dt) = super::datetime(b"\x76\x0B\x0D\x09\x23\x2D\x34").unwrap();
+        assert_eq!(dt.offset().local_minus_utc(), 13 * 3600);
+        assert!(super::datetime(b"\x76\x0B\x0D\x09\x23\x2D\x35").is_err());
+        assert!(super::datetime(b"\x76\x0B\x0D\x09\x23\x2D\xCF").is_err());
+    }
+
+    #[test]
+    fn test_optional_datetime() {
+        assert_eq!(super::optional_datetime(b"\0\0\0\0\0\0\0").unwrap().1, None);
+        assert_eq!(super::optional_datetime(b"\x76\x02\x1E\0\0\0\0").unwrap().1, None);
+        assert_eq!(super::optional_datetime(b"\x76\x0B\x0D\x18\0\0\0").unwrap().1, None);
+        assert!(super::optional_datetime(b"\x76\x0B\x0D\x09\x23\x2D\x01").unwrap().1.is_some());
+        assert!(super::datetime(b"\0\0\0\0\0\0\0").is_err());
     }
 
 }
@@ -0,0 +1,155 @@
+//! Rock Ridge Interchange Protocol (IEEE P1282) extensions.
+
+mod parser;
+
+use std::path::PathBuf;
+
+use chrono::DateTime;
+use chrono::offset::FixedOffset;
+
+use super::susp;
+use super::susp::Entry;
+
+/// POSIX file attributes, as recorded in a `PX` entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PosixAttributes {
+    /// The file mode, i.e. the file type and permission bits.
+    pub mode: u32,
+    /// The number of hard links to the file.
+    pub nlink: u32,
+    /// The user ID of the file owner.
+    pub uid: u32,
+    /// The group ID of the file owner.
+    pub gid: u32,
+    /// The file serial number (only recorded since RRIP 1.12).
+    pub inode: Option<u32>,
+}
+
+/// File timestamps, as recorded in a `TF` entry.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Timestamps {
+    pub creation: Option<DateTime<FixedOffset>>,
+    pub modify: Option<DateTime<FixedOffset>>,
+    pub access: Option<DateTime<FixedOffset>>,
+    pub attributes: Option<DateTime<FixedOffset>>,
+    pub backup: Option<DateTime<FixedOffset>>,
+    pub expiration: Option<DateTime<FixedOffset>>,
+    pub effective: Option<DateTime<FixedOffset>>,
+}
+
+/// The parameters of a file compressed with zisofs, as recorded in a `ZF`
+/// entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Zisofs {
+    /// The compression algorithm, `pz` for zlib.
+    pub algorithm: [u8; 2],
+    /// The size of the compressed file header, in 4-byte words.
+    pub header_size: u8,
+    /// The binary logarithm of the compression block size.
+    pub block_size_log2: u8,
+    /// The size of the uncompressed file.
+    pub size: u32,
+}
+
+impl Zisofs {
+    /// Check whether the file can be decompressed transparently.
+    pub fn is_supported(&self) -> bool {
+        &self.algorithm == b"pz"
+    }
+}
+
+/// Rock Ridge extensions found in the system use area of a directory record.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RockRidge {
+    /// The alternate name of the file (`NM`).
+    pub name: Option<String>,
+    /// The POSIX file attributes (`PX`).
+    pub posix: Option<PosixAttributes>,
+    /// The target of a symbolic link (`SL`).
+    pub symlink: Option<PathBuf>,
+    /// The file timestamps (`TF`).
+    pub timestamps: Timestamps,
+    /// The high and low words of a device number (`PN`).
+    pub device: Option<(u32, u32)>,
+    /// The location of a relocated directory this entry stands for (`CL`).
+    pub child_link: Option<u32>,
+    /// The location of the original parent of a relocated directory (`PL`).
+    pub parent_link: Option<u32>,
+    /// Whether this entry is a relocated directory (`RE`).
+    pub relocated: bool,
+    /// The zisofs compression parameters of the file (`ZF`).
+    pub zisofs: Option<Zisofs>,
+}
+
+impl RockRidge {
+    /// Decode the Rock Ridge entries of a system use area.
+    ///
+    /// Continuation areas are not followed: use [`from_entries`] with
+    /// entries read by [`susp::read`] to decode a complete system use area.
+    ///
+    /// [`from_entries`]: #method.from_entries
+    /// [`susp::read`]: ../susp/fn.read.html
+    pub fn parse(system_use: &[u8]) -> Option<Self> {
+        Self::from_entries(&susp::parse(system_use, 0))
+    }
+
+    /// Decode the Rock Ridge entries among the given SUSP entries.
+    ///
+    /// Returns `None` if there is no Rock Ridge entry. Malformed entries
+    /// are ignored instead of causing an error, since the system use area
+    /// may be shared with other extensions.
+    pub fn from_entries(entries: &[Entry]) -> Option<Self> {
+        let mut rr = Self::default();
+        let mut found = false;
+        let mut name: Option<Vec<u8>> = None;
+        let mut components: Option<Vec<(u8, &[u8])>> = None;
+
+        for entry in entries {
+            let (signature, data) = match *entry {
+                Entry::Unknown { ref signature, ref data, .. } => (signature, data.as_slice()),
+                _ => continue,
+            };
+            found |= match signature {
+                b"NM" => parser::alternate_name(data)
+                    .map(|(_, part)| name.get_or_insert_with(Vec::new).extend(part))
+                    .is_ok(),
+                b"PX" => parser::posix_attributes(data)
+                    .map(|(_, px)| rr.posix = Some(px))
+                    .is_ok(),
+                b"SL" => parser::symbolic_link(data)
+                    .map(|(_, sl)| components.get_or_insert_with(Vec::new).extend(sl))
+                    .is_ok(),
+                b"TF" => parser::timestamps(data)
+                    .map(|(_, tf)| rr.timestamps = tf)
+                    .is_ok(),
+                b"PN" => parser::device_number(data)
+                    .map(|(_, pn)| rr.device = Some(pn))
+                    .is_ok(),
+                b"CL" => parser::location(data)
+                    .map(|(_, cl)| rr.child_link = Some(cl))
+                    .is_ok(),
+                b"PL" => parser::location(data)
+                    .map(|(_, pl)| rr.parent_link = Some(pl))
+                    .is_ok(),
+                b"ZF" => parser::zisofs(data)
+                    .map(|(_, zf)| rr.zisofs = Some(zf))
+                    .is_ok(),
+                b"RE" => {
+                    rr.relocated = true;
+                    true
+                }
+                b"RR" => true,
+                _ => false,
+            };
+        }
+
+        rr.name = name.map(|n| String::from_utf8_lossy(&n).into_owned());
+        rr.symlink = components.map(|c| parser::link_target(&c));
+
+        if found {
+            Some(rr)
+        } else {
+            None
+        }
+    }
+}
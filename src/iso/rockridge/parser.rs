@@ -0,0 +1,236 @@
+use std::path::PathBuf;
+
+use nom::be_u8;
+
+use utils::parsers::both_u32;
+
+use super::super::descriptors::optional_datetime as long_datetime;
+use super::super::record::parser::optional_datetime as short_datetime;
+
+use super::PosixAttributes;
+use super::Timestamps;
+use super::Zisofs;
+
+/// The `CONTINUE` flag of `NM` and `SL` entries, and of `SL` components.
+const CONTINUE: u8 = 0x01;
+/// The `CURRENT` flag of `NM` entries and `SL` components.
+const CURRENT: u8 = 0x02;
+/// The `PARENT` flag of `NM` entries and `SL` components.
+const PARENT: u8 = 0x04;
+/// The `ROOT` flag of `SL` components.
+const ROOT: u8 = 0x08;
+/// The `LONG_FORM` flag of `TF` entries.
+const LONG_FORM: u8 = 0x80;
+
+#[rustfmt::skip]
+named!(pub alternate_name(&[u8]) -> &[u8],
+    do_parse!(
+        flags:  be_u8                                        >>
+        name:   cond!(flags & (CURRENT | PARENT) == 0, call!(::nom::rest)) >>
+                (name.unwrap_or(b""))
+    )
+);
+
+#[rustfmt::skip]
+named!(pub posix_attributes(&[u8]) -> PosixAttributes,
+    do_parse!(
+        mode:   both_u32                                     >>
+        nlink:  both_u32                                     >>
+        uid:    both_u32                                     >>
+        gid:    both_u32                                     >>
+        inode:  opt!(complete!(both_u32))                    >>
+                (PosixAttributes { mode, nlink, uid, gid, inode })
+    )
+);
+
+#[rustfmt::skip]
+named!(component(&[u8]) -> (u8, &[u8]),
+    do_parse!(
+        flags:   be_u8                                       >>
+        content: length_data!(be_u8)                         >>
+                 (flags, content)
+    )
+);
+
+#[rustfmt::skip]
+named!(pub symbolic_link(&[u8]) -> Vec<(u8, &[u8])>,
+    do_parse!(
+                    be_u8                                    >>
+        components: many0!(complete!(component))             >>
+                    (components)
+    )
+);
+
+/// Build the target of a symbolic link from its `SL` components.
+pub fn link_target(components: &[(u8, &[u8])]) -> PathBuf {
+    let mut path = PathBuf::new();
+    let mut pending: Vec<u8> = Vec::new();
+    for &(flags, content) in components {
+        if flags & ROOT != 0 {
+            path.push("/");
+        } else if flags & CURRENT != 0 {
+            path.push(".");
+        } else if flags & PARENT != 0 {
+            path.push("..");
+        } else {
+            pending.extend(content);
+            if flags & CONTINUE == 0 {
+                path.push(String::from_utf8_lossy(&pending).as_ref());
+                pending.clear();
+            }
+        }
+    }
+    path
+}
+
+#[rustfmt::skip]
+named!(pub timestamps(&[u8]) -> Timestamps,
+    do_parse!(
+        flags:       be_u8                                   >>
+        creation:    apply!(timestamp, flags, 0)             >>
+        modify:      apply!(timestamp, flags, 1)             >>
+        access:      apply!(timestamp, flags, 2)             >>
+        attributes:  apply!(timestamp, flags, 3)             >>
+        backup:      apply!(timestamp, flags, 4)             >>
+        expiration:  apply!(timestamp, flags, 5)             >>
+        effective:   apply!(timestamp, flags, 6)             >>
+                     (Timestamps {
+                         creation,
+                         modify,
+                         access,
+                         attributes,
+                         backup,
+                         expiration,
+                         effective,
+                     })
+    )
+);
+
+/// Parse the `n`-th timestamp of a `TF` entry, if it is recorded.
+fn timestamp(
+    input: &[u8],
+    flags: u8,
+    n: u8,
+) -> ::nom::IResult<&[u8], Option<::chrono::DateTime<::chrono::FixedOffset>>> {
+    if flags & (1 << n) == 0 {
+        Ok((input, None))
+    } else if flags & LONG_FORM != 0 {
+        long_datetime(input)
+    } else {
+        short_datetime(input)
+    }
+}
+
+#[rustfmt::skip]
+named!(pub device_number(&[u8]) -> (u32, u32),
+    do_parse!(
+        high:   both_u32                                     >>
+        low:    both_u32                                     >>
+                (high, low)
+    )
+);
+
+#[rustfmt::skip]
+named!(pub location(&[u8]) -> u32,
+    call!(both_u32)
+);
+
+#[rustfmt::skip]
+named!(pub zisofs(&[u8]) -> Zisofs,
+    do_parse!(
+        algorithm:       take!(2)                            >>
+        header_size:     be_u8                               >>
+        block_size_log2: be_u8                               >>
+        size:            both_u32                            >>
+                         (Zisofs {
+                             algorithm: [algorithm[0], algorithm[1]],
+                             header_size,
+                             block_size_log2,
+                             size,
+                         })
+    )
+);
+
+#[cfg(test)]
+mod tests {
+
+    use std::path::Path;
+
+    use chrono::Datelike;
+
+    use super::super::RockRidge;
+
+    #[test]
+    fn test_posix_attributes() {
+        let su = b"PX$\x01\xedA\x00\x00\x00\x00A\xed\x01\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00";
+        let rr = RockRidge::parse(&su[..]).unwrap();
+        let px = rr.posix.unwrap();
+        assert_eq!(px.mode, 0o40755);
+        assert_eq!(px.nlink, 1);
+        assert_eq!(px.uid, 0);
+        assert_eq!(px.inode, None);
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let su = b"TF\x1a\x01\x0ev\x04\x01\x05\x05\x17\x00v\x04\x01\x05\x05\x18\x00v\x04\x01\x05\x05\x17\x00";
+        let rr = RockRidge::parse(&su[..]).unwrap();
+        assert_eq!(rr.timestamps.creation, None);
+        assert_eq!(rr.timestamps.modify.unwrap().year(), 2018);
+        assert!(rr.timestamps.access.is_some());
+        assert!(rr.timestamps.attributes.is_some());
+        assert_eq!(rr.timestamps.backup, None);
+    }
+
+    #[test]
+    fn test_alternate_name() {
+        let su = b"NM\x0a\x01\x01long-NM\x09\x01\x00name\x00\x00";
+        let rr = RockRidge::parse(&su[..]).unwrap();
+        assert_eq!(rr.name, Some(String::from("long-name")));
+    }
+
+    #[test]
+    fn test_symbolic_link() {
+        let su = b"SL\x11\x01\x01\x08\x00\x00\x03usr\x00\x03libSL\x0a\x01\x00\x00\x03bin";
+        let rr = RockRidge::parse(&su[..]).unwrap();
+        assert_eq!(rr.symlink.unwrap(), Path::new("/usr/lib/bin"));
+    }
+
+    #[test]
+    fn test_device_number() {
+        let su = b"PN\x14\x01\x00\x00\x00\x00\x00\x00\x00\x00\x05\x00\x00\x00\x00\x00\x00\x05";
+        let rr = RockRidge::parse(&su[..]).unwrap();
+        assert_eq!(rr.device, Some((0, 5)));
+    }
+
+    #[test]
+    fn test_relocation() {
+        let su = b"CL\x0c\x01\x2a\x00\x00\x00\x00\x00\x00\x2a";
+        let rr = RockRidge::parse(&su[..]).unwrap();
+        assert_eq!(rr.child_link, Some(42));
+        assert!(!rr.relocated);
+
+        let su = b"PL\x0c\x01\x18\x00\x00\x00\x00\x00\x00\x18";
+        let rr = RockRidge::parse(&su[..]).unwrap();
+        assert_eq!(rr.parent_link, Some(24));
+
+        let rr = RockRidge::parse(b"RE\x04\x01").unwrap();
+        assert!(rr.relocated);
+    }
+
+    #[test]
+    fn test_zisofs() {
+        let su = b"ZF\x10\x01pz\x04\x0f\x00\x10\x00\x00\x00\x00\x10\x00";
+        let zf = RockRidge::parse(&su[..]).unwrap().zisofs.unwrap();
+        assert!(zf.is_supported());
+        assert_eq!(zf.header_size, 4);
+        assert_eq!(zf.block_size_log2, 15);
+        assert_eq!(zf.size, 4096);
+    }
+
+    #[test]
+    fn test_no_rock_ridge() {
+        assert_eq!(RockRidge::parse(b""), None);
+        assert_eq!(RockRidge::parse(b"\x00\x00\x00\x00\x0d\x55XA\x01\x00\x00\x00\x00\x00"), None);
+    }
+}
@@ -0,0 +1,167 @@
+use std::io;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+use super::super::error::Error;
+use super::super::error::ErrorKind;
+use super::super::error::Result;
+
+use super::constants::SECTOR_SIZE;
+use super::descriptors::VolumeDescriptor;
+use super::descriptors::VolumeInfo;
+
+/// The number of sectors read at once when scanning for a session.
+const SCAN_CHUNK: u64 = 32;
+
+/// The descriptors of a volume descriptor set, with their sector.
+type DescriptorSet = Vec<(u32, VolumeDescriptor)>;
+
+/// A session with the descriptors of its volume descriptor set.
+type SessionSet = (Session, DescriptorSet);
+
+/// A session of a multisession disc.
+///
+/// Each session records its own volume descriptor set, 16 sectors after
+/// the start of the session. Extents are absolute on the disc, so the
+/// hierarchy of a later session can reference files from earlier ones.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Session {
+    /// The first sector of the session.
+    pub start: u32,
+    /// The information recorded in the Primary Volume Descriptor of the
+    /// session, or `None` for a session listed in a table of contents that
+    /// has no readable Primary Volume Descriptor (e.g. an audio session).
+    pub info: Option<VolumeInfo>,
+}
+
+/// Read the volume descriptor set of the session starting at `start`.
+///
+/// Descriptors are returned with the absolute number of the sector they
+/// were read from.
+pub(in iso) fn read_set<H: Read + Seek>(
+    handle: &mut H,
+    start: u32,
+) -> Result<DescriptorSet> {
+    let offset = start as u64 + 0x10;
+    let mut buff = [0; SECTOR_SIZE as usize];
+    let mut descriptors: DescriptorSet = Vec::new();
+
+    // Go to the 16th logical sector of the session
+    handle.seek(SeekFrom::Start(offset * SECTOR_SIZE))?;
+
+    // Read all volume descriptors until the set terminator, only reporting
+    // the sectors that are not volume descriptors as a missing set
+    loop {
+        handle.read_exact(&mut buff)?;
+        let vd = VolumeDescriptor::parse(&buff)
+            .map_err(|_| Error::from(ErrorKind::NoPrimaryVolumeDescriptor))?;
+        let terminated = vd.descriptor_type() == 0xFF;
+        descriptors.push((offset as u32 + descriptors.len() as u32, vd));
+        if terminated {
+            return Ok(descriptors);
+        }
+    }
+}
+
+/// Find the sessions of the disc, with their volume descriptor set.
+///
+/// When `toc` is given, only the sessions starting at the listed sectors
+/// are read, and sessions without a readable Primary Volume Descriptor
+/// (e.g. audio sessions) are kept without descriptors so that indices
+/// match the table of contents. Otherwise, the handle is scanned for volume
+/// descriptor sets located after the volume space of the previous session,
+/// skipping the ones without a Primary Volume Descriptor. A scanned set
+/// that ends with the image (e.g. a stray descriptor sector or a truncated
+/// session) ends the scan, but I/O errors are reported for the sessions of
+/// the table of contents and for the first session.
+pub(in iso) fn discover<H: Read + Seek>(
+    handle: &mut H,
+    toc: Option<&[u32]>,
+) -> Result<Vec<SessionSet>> {
+    let mut sessions = Vec::new();
+
+    if let Some(starts) = toc {
+        for &start in starts {
+            let session = match read_session(handle, start)? {
+                Some((info, descriptors)) => (Session { start, info: Some(info) }, descriptors),
+                None => (Session { start, info: None }, Vec::new()),
+            };
+            sessions.push(session);
+        }
+        return Ok(sessions);
+    }
+
+    let mut candidate = Some(0);
+    while let Some(start) = candidate {
+        // the next session cannot start inside the volume space of this one
+        let end = match read_session(handle, start) {
+            Ok(Some((info, descriptors))) => {
+                let set_end = start + 0x10 + descriptors.len() as u32;
+                let end = set_end.max(info.volume_space_size);
+                sessions.push((Session { start, info: Some(info) }, descriptors));
+                end
+            }
+            Ok(None) => start + 1,
+            Err(Error(ErrorKind::Io(ref err), _))
+                if start > 0 && err.kind() == io::ErrorKind::UnexpectedEof =>
+            {
+                break
+            }
+            Err(err) => return Err(err),
+        };
+        candidate = scan(handle, end)?;
+    }
+
+    Ok(sessions)
+}
+
+/// Read the session starting at `start`, if it has a Primary Volume
+/// Descriptor.
+fn read_session<H: Read + Seek>(
+    handle: &mut H,
+    start: u32,
+) -> Result<Option<(VolumeInfo, DescriptorSet)>> {
+    let descriptors = match read_set(handle, start) {
+        Ok(descriptors) => descriptors,
+        Err(Error(ErrorKind::NoPrimaryVolumeDescriptor, _)) => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let info = descriptors
+        .iter()
+        .filter_map(|(_, descriptor)| match *descriptor {
+            VolumeDescriptor::Primary(ref pvd) => Some(pvd.info.clone()),
+            _ => None,
+        })
+        .next();
+    Ok(info.map(|info| (info, descriptors)))
+}
+
+/// Check whether a sector holds a Primary Volume Descriptor, or the
+/// Standard File Structure Volume Descriptor of a High Sierra volume.
+fn is_primary(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"\x01CD001") || bytes.get(8..14) == Some(b"\x01CDROM")
+}
+
+/// Find the start of the first session beginning at or after `from`.
+fn scan<H: Read + Seek>(handle: &mut H, from: u32) -> Result<Option<u32>> {
+    let length = handle.seek(SeekFrom::End(0))? / SECTOR_SIZE;
+    let mut sector = from as u64 + 0x10;
+    let mut buff = vec![0; (SCAN_CHUNK * SECTOR_SIZE) as usize];
+
+    handle.seek(SeekFrom::Start(sector * SECTOR_SIZE))?;
+    while sector < length {
+        let count = SCAN_CHUNK.min(length - sector);
+        let chunk = &mut buff[..(count * SECTOR_SIZE) as usize];
+        handle.read_exact(chunk)?;
+        let found = chunk
+            .chunks(SECTOR_SIZE as usize)
+            .position(is_primary);
+        if let Some(index) = found {
+            return Ok(Some((sector + index as u64 - 0x10) as u32));
+        }
+        sector += count;
+    }
+
+    Ok(None)
+}
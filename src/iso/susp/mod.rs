@@ -0,0 +1,126 @@
+//! System Use Sharing Protocol (IEEE P1281) entries.
+//!
+//! The SUSP defines how the system use area found after the file identifier
+//! of a directory record is shared between several extensions, such as
+//! [Rock Ridge](../struct.RockRidge.html). Entries that are not defined by
+//! the SUSP itself are exposed raw, so that they can be decoded by the
+//! relevant extension.
+
+mod parser;
+
+use std::cmp::min;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+use utils::charsets::Charset;
+
+use super::super::error::Result;
+use super::constants::SECTOR_SIZE;
+use super::record::Format;
+use super::record::Record;
+
+/// The maximum number of continuation areas followed for a single record.
+///
+/// This prevents looping forever on images with a cyclic chain of `CE`
+/// entries.
+const MAX_CONTINUATIONS: usize = 64;
+
+/// An entry of a system use area.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Entry {
+    /// Indicates the SUSP is in use on the volume (`SP`).
+    ///
+    /// `skip` is the number of bytes to skip at the start of every system
+    /// use area before reading entries.
+    SharingProtocol { skip: u8 },
+    /// Points to a continuation area holding additional entries (`CE`).
+    Continuation { block: u32, offset: u32, length: u32 },
+    /// Identifies an extension used on the volume (`ER`).
+    ExtensionReference {
+        version: u8,
+        identifier: String,
+        descriptor: String,
+        source: String,
+    },
+    /// Selects the extension the following entries belong to (`ES`).
+    ExtensionSelector { sequence: u8 },
+    /// Padding bytes (`PD`).
+    Padding { length: u8 },
+    /// Terminates the system use entries (`ST`).
+    Terminator,
+    /// An entry not defined by the SUSP, such as an extension entry.
+    Unknown {
+        signature: [u8; 2],
+        version: u8,
+        data: Vec<u8>,
+    },
+}
+
+/// Parse the entries of a system use area, without following continuations.
+///
+/// `skip` bytes are ignored at the start of the area. Parsing stops at the
+/// first `ST` entry, which is included in the returned entries, or at the
+/// first entry whose length is inconsistent with the remaining data.
+pub fn parse(system_use: &[u8], skip: u8) -> Vec<Entry> {
+    system_use
+        .get(skip as usize..)
+        .map(parser::entries)
+        .unwrap_or_default()
+}
+
+/// Read all the entries of a system use area, following continuations.
+///
+/// Entries of a continuation area are inserted after the entries of the
+/// area that contains the `CE` entry pointing to it, which is read from
+/// `handle`. Since a continuation area may not span several logical
+/// blocks, it is truncated at the end of the block it starts in.
+pub fn read<H>(system_use: &[u8], skip: u8, handle: &mut H) -> Result<Vec<Entry>>
+where
+    H: Read + Seek,
+{
+    let mut entries = parse(system_use, skip);
+    let mut index = 0;
+    let mut continuations = 0;
+
+    while index < entries.len() && continuations < MAX_CONTINUATIONS {
+        if let Entry::Continuation { block, offset, length } = entries[index] {
+            let start = block as u64 * SECTOR_SIZE + offset as u64;
+            let length = min(length as u64, SECTOR_SIZE - start % SECTOR_SIZE);
+            let mut area = vec![0; length as usize];
+            handle.seek(SeekFrom::Start(start))?;
+            handle.read_exact(&mut area)?;
+            let continued = parser::entries(&area);
+            let tail = entries.split_off(index + 1);
+            entries.extend(continued);
+            entries.extend(tail);
+            continuations += 1;
+        }
+        index += 1;
+    }
+
+    Ok(entries)
+}
+
+/// Check whether the SUSP is in use in the hierarchy rooted at `root`.
+///
+/// The SUSP is in use if the system use area of the first record of the
+/// root directory (its `.` entry) starts with an `SP` entry, in which case
+/// the number of bytes to skip in every other system use area is returned.
+pub(in iso) fn detect<H>(root: &Record, handle: &mut H) -> Result<Option<u8>>
+where
+    H: Read + Seek,
+{
+    use super::record::parser::record;
+
+    let mut buffer = [0; SECTOR_SIZE as usize];
+    let start = root.extent as u64 + root.ear_length as u64;
+    handle.seek(SeekFrom::Start(start * SECTOR_SIZE))?;
+    handle.read_exact(&mut buffer)?;
+
+    let (_, dot) = record(&buffer, Format::Iso(Charset::default()))?;
+    match parse(&dot.system_use, 0).first() {
+        Some(&Entry::SharingProtocol { skip }) => Ok(Some(skip)),
+        _ => Ok(None),
+    }
+}
@@ -0,0 +1,164 @@
+use nom::be_u8;
+
+use utils::parsers::both_u32;
+
+use super::Entry;
+
+#[rustfmt::skip]
+named!(sharing_protocol(&[u8]) -> Entry,
+    do_parse!(
+                tag!(b"\xBE\xEF")                            >>
+        skip:   be_u8                                        >>
+                (Entry::SharingProtocol { skip })
+    )
+);
+
+#[rustfmt::skip]
+named!(continuation(&[u8]) -> Entry,
+    do_parse!(
+        block:  both_u32                                     >>
+        offset: both_u32                                     >>
+        length: both_u32                                     >>
+                (Entry::Continuation { block, offset, length })
+    )
+);
+
+#[rustfmt::skip]
+named!(extension_reference(&[u8]) -> Entry,
+    do_parse!(
+        len_id:     be_u8                                    >>
+        len_des:    be_u8                                    >>
+        len_src:    be_u8                                    >>
+        version:    be_u8                                    >>
+        identifier: take!(len_id)                            >>
+        descriptor: take!(len_des)                           >>
+        source:     take!(len_src)                           >>
+                    (Entry::ExtensionReference {
+                        version,
+                        identifier: String::from_utf8_lossy(identifier).into_owned(),
+                        descriptor: String::from_utf8_lossy(descriptor).into_owned(),
+                        source: String::from_utf8_lossy(source).into_owned(),
+                    })
+    )
+);
+
+#[rustfmt::skip]
+named!(extension_selector(&[u8]) -> Entry,
+    do_parse!(
+        sequence:   be_u8                                    >>
+                    (Entry::ExtensionSelector { sequence })
+    )
+);
+
+/// Decode a single entry from its signature, version and data.
+fn entry(signature: &[u8], version: u8, data: &[u8]) -> Entry {
+    let parsed = match signature {
+        b"SP" => sharing_protocol(data).ok(),
+        b"CE" => continuation(data).ok(),
+        b"ER" => extension_reference(data).ok(),
+        b"ES" => extension_selector(data).ok(),
+        b"PD" => Some((data, Entry::Padding { length: data.len() as u8 + 4 })),
+        b"ST" => Some((data, Entry::Terminator)),
+        _ => None,
+    };
+    match parsed {
+        Some((_, entry)) => entry,
+        None => Entry::Unknown {
+            signature: [signature[0], signature[1]],
+            version,
+            data: data.to_vec(),
+        },
+    }
+}
+
+/// Split a system use area into entries.
+pub fn entries(mut input: &[u8]) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    while input.len() >= 4 {
+        let length = input[2] as usize;
+        if length < 4 || length > input.len() {
+            break;
+        }
+        let entry = entry(&input[..2], input[3], &input[4..length]);
+        let terminated = entry == Entry::Terminator;
+        entries.push(entry);
+        if terminated {
+            break;
+        }
+        input = &input[length..];
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::io::Cursor;
+
+    use super::super::Entry;
+
+    #[test]
+    fn test_sharing_protocol() {
+        let entries = super::entries(b"SP\x07\x01\xbe\xef\x00");
+        assert_eq!(entries, vec![Entry::SharingProtocol { skip: 0 }]);
+        // bad check bytes
+        let entries = super::entries(b"SP\x07\x01\x00\x00\x00");
+        match entries[0] {
+            Entry::Unknown { ref signature, .. } => assert_eq!(signature, b"SP"),
+            ref other => panic!("unexpected entry: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_extension_reference() {
+        let entries = super::entries(b"ER\x15\x01\x0a\x03\x00\x01RRIP_1991ADESNM\x00");
+        assert_eq!(entries.len(), 1);
+        match entries[0] {
+            Entry::ExtensionReference { ref identifier, ref descriptor, version, .. } => {
+                assert_eq!(identifier, "RRIP_1991A");
+                assert_eq!(descriptor, "DES");
+                assert_eq!(version, 1);
+            }
+            ref other => panic!("unexpected entry: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_terminator() {
+        let entries = super::entries(b"PD\x06\x01\x00\x00ST\x04\x01NM\x05\x01\x00");
+        assert_eq!(entries, vec![Entry::Padding { length: 6 }, Entry::Terminator]);
+    }
+
+    #[test]
+    fn test_skip() {
+        let entries = super::super::parse(b"\xff\xffES\x05\x01\x02", 2);
+        assert_eq!(entries, vec![Entry::ExtensionSelector { sequence: 2 }]);
+        assert!(super::super::parse(b"", 4).is_empty());
+    }
+
+    #[test]
+    fn test_continuation() {
+        let mut image = vec![0; 2048 * 2];
+        image[2048 + 16..2048 + 25].copy_from_slice(b"NM\x09\x01\x00name");
+        let area = b"CE\x1c\x01\x01\x00\x00\x00\x00\x00\x00\x01\x10\x00\x00\x00\x00\x00\x00\x10\x09\x00\x00\x00\x00\x00\x00\x09ST\x04\x01";
+        let entries = super::super::read(&area[..], 0, &mut Cursor::new(image)).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[1], Entry::Unknown {
+            signature: *b"NM",
+            version: 1,
+            data: b"\x00name".to_vec(),
+        });
+        assert_eq!(entries[2], Entry::Terminator);
+    }
+
+    #[test]
+    fn test_continuation_length() {
+        // a continuation area is truncated at the end of its block
+        let mut image = vec![0; 2048 * 2];
+        image[2048 + 2040..2048 + 2044].copy_from_slice(b"ST\x04\x01");
+        let area = b"CE\x1c\x01\x01\x00\x00\x00\x00\x00\x00\x01\xf8\x07\x00\x00\x00\x00\x07\xf8\xff\xff\xff\xff\xff\xff\xff\xff";
+        let entries = super::super::read(&area[..], 0, &mut Cursor::new(image)).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1], Entry::Terminator);
+    }
+}
@@ -0,0 +1,171 @@
+use std::io::Read;
+use std::io::Seek;
+use std::path::Path;
+
+use super::super::error::Error;
+use super::super::error::ErrorKind;
+use super::super::error::Result;
+
+use super::file::IsoFile;
+use super::metadata::Metadata;
+use super::IsoFs;
+
+/// A set of ISO-9660 volumes sharing a volume set identifier.
+///
+/// The directory hierarchy of the volume with the highest sequence number
+/// applies to the whole set, and its records may describe files recorded
+/// on any volume with a lower sequence number. An `IsoVolumeSet` browses
+/// that hierarchy, and reads each file from the volume named by the
+/// sequence number of its directory record.
+///
+/// # Example
+///
+/// ```rust
+/// use opticaldisc::iso::IsoVolumeSet;
+///
+/// let path = "static/iso/alpine.level1.iso";
+/// let mut set = IsoVolumeSet::from_paths(&[path]).unwrap();
+/// assert!(set.is_complete());
+/// assert!(set.is_file("/ETC/APK/ARCH"));
+/// ```
+pub struct IsoVolumeSet<H: Read + Seek> {
+    volumes: Vec<IsoFs<H>>,
+}
+
+// Constructor
+impl<H: Read + Seek> IsoVolumeSet<H> {
+    /// Create a volume set from the given volumes, in any order.
+    ///
+    /// # Errors
+    ///
+    /// * [`EmptyVolumeSet`](../error/enum.ErrorKind.html#variant.EmptyVolumeSet)
+    ///   when no volume is given
+    /// * [`VolumeSetMismatch`](../error/enum.ErrorKind.html#variant.VolumeSetMismatch)
+    ///   when the volumes do not share the same volume set identifier
+    /// * [`DuplicateVolume`](../error/enum.ErrorKind.html#variant.DuplicateVolume)
+    ///   when two volumes have the same sequence number
+    pub fn new(mut volumes: Vec<IsoFs<H>>) -> Result<Self> {
+        volumes.sort_by_key(|volume| volume.volume_info().volume_sequence_number);
+
+        let expected = match volumes.first() {
+            Some(volume) => volume.volume_info().volume_set_id.clone(),
+            None => bail!(ErrorKind::EmptyVolumeSet),
+        };
+        for volume in &volumes {
+            let found = &volume.volume_info().volume_set_id;
+            if *found != expected {
+                bail!(ErrorKind::VolumeSetMismatch(expected, found.clone()));
+            }
+        }
+        for pair in volumes.windows(2) {
+            let sequence_number = pair[0].volume_info().volume_sequence_number;
+            if pair[1].volume_info().volume_sequence_number == sequence_number {
+                bail!(ErrorKind::DuplicateVolume(sequence_number));
+            }
+        }
+
+        Ok(Self { volumes })
+    }
+}
+
+// Constructor from files
+impl IsoVolumeSet<::std::fs::File> {
+    /// Open a volume set from the images located at the given paths.
+    pub fn from_paths<P: AsRef<Path>>(paths: &[P]) -> Result<Self> {
+        paths
+            .iter()
+            .map(IsoFs::from_path)
+            .collect::<Result<Vec<_>>>()
+            .and_then(Self::new)
+    }
+}
+
+// Common methods
+impl<H: Read + Seek> IsoVolumeSet<H> {
+    /// Get the volume set identifier shared by all volumes.
+    pub fn volume_set_id(&self) -> &str {
+        &self.volumes[0].volume_info().volume_set_id
+    }
+
+    /// Get the number of volumes of the set, as recorded on its volumes.
+    pub fn volume_set_size(&self) -> u16 {
+        self.last().volume_info().volume_set_size
+    }
+
+    /// Check whether all the volumes of the set were given.
+    pub fn is_complete(&self) -> bool {
+        let size = self.volume_set_size() as usize;
+        self.volumes.len() == size
+            && self
+                .volumes
+                .iter()
+                .zip(1..)
+                .all(|(volume, n)| volume.volume_info().volume_sequence_number == n)
+    }
+
+    /// Get the volumes of the set, ordered by sequence number.
+    pub fn volumes(&self) -> &[IsoFs<H>] {
+        &self.volumes
+    }
+
+    /// Get the volume with the given sequence number, if it was given.
+    pub fn volume(&mut self, sequence_number: u16) -> Option<&mut IsoFs<H>> {
+        self.volumes
+            .iter_mut()
+            .find(|volume| volume.volume_info().volume_sequence_number == sequence_number)
+    }
+
+    /// Get the volume whose directory hierarchy is browsed.
+    fn last(&self) -> &IsoFs<H> {
+        &self.volumes[self.volumes.len() - 1]
+    }
+
+    fn last_mut(&mut self) -> &mut IsoFs<H> {
+        let index = self.volumes.len() - 1;
+        &mut self.volumes[index]
+    }
+
+    /// Read the contents of the directory located at the given path.
+    pub fn read_dir<P: AsRef<Path>>(&mut self, path: P) -> Result<Vec<Metadata>> {
+        self.last_mut().read_dir(path)
+    }
+
+    /// Get the metadata of the resource located at the given path.
+    pub fn metadata<P: AsRef<Path>>(&mut self, path: P) -> Result<Metadata> {
+        self.last_mut().metadata(path)
+    }
+
+    /// Check if the given path maps to a directory of the volume set.
+    pub fn is_dir<P: AsRef<Path>>(&mut self, path: P) -> bool {
+        self.last_mut().is_dir(path)
+    }
+
+    /// Check if the given path maps to a file of the volume set.
+    pub fn is_file<P: AsRef<Path>>(&mut self, path: P) -> bool {
+        self.last_mut().is_file(path)
+    }
+
+    /// Check if the given path exists in the volume set.
+    pub fn exists<P: AsRef<Path>>(&mut self, path: P) -> bool {
+        self.last_mut().exists(path)
+    }
+
+    /// Open the file located at the given path, reading it from the volume
+    /// it is recorded on.
+    ///
+    /// # Errors
+    ///
+    /// * [`VolumeNotFound`](../error/enum.ErrorKind.html#variant.VolumeNotFound)
+    ///   when the volume storing the file is not part of the set
+    pub fn open_file<'a, P: AsRef<Path>>(&'a mut self, path: P) -> Result<IsoFile<'a, H>> {
+        let node = self.last_mut().node(path.as_ref())?;
+        let volume = match node.record.seq_number {
+            0 => self.last_mut(),
+            n => match self.volume(n) {
+                Some(volume) => volume,
+                None => bail!(ErrorKind::VolumeNotFound(n)),
+            },
+        };
+        IsoFile::new(&mut volume.handle, &node.record, volume.block_size).map_err(Error::from)
+    }
+}
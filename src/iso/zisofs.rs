@@ -0,0 +1,244 @@
+use std::io::Error;
+use std::io::ErrorKind::InvalidData;
+use std::io::Read;
+use std::io::Result;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+use flate2::read::ZlibDecoder;
+
+use nom::le_u32;
+
+use utils::io::seek_position;
+
+/// The magic number starting the header of a zisofs compressed file.
+const MAGIC: [u8; 8] = [0x37, 0xE4, 0x53, 0x96, 0xC9, 0xDB, 0xD6, 0x07];
+
+/// A reader decompressing a file compressed with zisofs.
+///
+/// The file is made of a header, a table of pointers to the compressed
+/// blocks, and the blocks themselves, each compressed independently with
+/// zlib. This allows seeking by only decompressing the block containing the
+/// new position.
+pub(in iso) struct ZisofsReader<R: Read + Seek> {
+    inner: R,
+    size: u64,
+    block_log2: u8,
+    pointers: Vec<u32>,
+    pos: u64,
+    block: Option<(usize, Vec<u8>)>,
+}
+
+impl<R: Read + Seek> ZisofsReader<R> {
+    /// Read the header and block pointers of the compressed file in `inner`.
+    pub fn new(mut inner: R) -> Result<Self> {
+        let mut header = [0; 16];
+        inner.seek(SeekFrom::Start(0))?;
+        inner.read_exact(&mut header)?;
+
+        if header[..8] != MAGIC {
+            return Err(Error::new(InvalidData, "invalid zisofs header"));
+        }
+
+        let size = match le_u32(&header[8..12]) {
+            Ok((_, size)) => size as u64,
+            Err(_) => return Err(Error::new(InvalidData, "invalid zisofs header")),
+        };
+        let header_size = header[12] as u64 * 4;
+        let block_log2 = header[13];
+        if !(15..=17).contains(&block_log2) {
+            return Err(Error::new(InvalidData, "invalid zisofs block size"));
+        }
+
+        // the block pointers table must fit in the compressed file
+        let length = inner.seek(SeekFrom::End(0))?;
+        let table_size = (size.div_ceil(1 << block_log2) + 1) * 4;
+        if header_size + table_size > length {
+            return Err(Error::new(InvalidData, "truncated zisofs block pointers"));
+        }
+        let mut table = vec![0; table_size as usize];
+        inner.seek(SeekFrom::Start(header_size))?;
+        inner.read_exact(&mut table)?;
+
+        Ok(Self {
+            inner,
+            size,
+            block_log2,
+            pointers: table
+                .chunks(4)
+                .filter_map(|chunk| le_u32(chunk).ok())
+                .map(|(_, pointer)| pointer)
+                .collect(),
+            pos: 0,
+            block: None,
+        })
+    }
+
+    /// Get the uncompressed size of the file.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Decompress the block of given `index`, unless it is already cached.
+    fn load_block(&mut self, index: usize) -> Result<()> {
+        if let Some((cached, _)) = self.block {
+            if cached == index {
+                return Ok(());
+            }
+        }
+
+        let block_size = 1u64 << self.block_log2;
+        let length = ::std::cmp::min(block_size, self.size - index as u64 * block_size);
+        let (start, end) = (self.pointers[index], self.pointers[index + 1]);
+
+        // zlib only expands incompressible data by a few bytes per block
+        if end < start || (end - start) as u64 > 2 * block_size {
+            return Err(Error::new(InvalidData, "invalid zisofs block pointers"));
+        }
+
+        // an empty block stands for a block filled with zeros
+        let mut data = Vec::with_capacity(length as usize);
+        if end > start {
+            let mut compressed = vec![0; (end - start) as usize];
+            self.inner.seek(SeekFrom::Start(start as u64))?;
+            self.inner.read_exact(&mut compressed)?;
+            ZlibDecoder::new(&compressed[..])
+                .take(length)
+                .read_to_end(&mut data)?;
+            if data.len() as u64 != length {
+                return Err(Error::new(InvalidData, "truncated zisofs block"));
+            }
+        } else {
+            data.resize(length as usize, 0);
+        }
+
+        self.block = Some((index, data));
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> Read for ZisofsReader<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        if self.pos >= self.size || buffer.is_empty() {
+            return Ok(0);
+        }
+
+        let index = (self.pos >> self.block_log2) as usize;
+        let offset = (self.pos - ((index as u64) << self.block_log2)) as usize;
+        self.load_block(index)?;
+
+        let block = &self.block.as_ref().unwrap().1;
+        let size = ::std::cmp::min(block.len() - offset, buffer.len());
+        buffer[..size].copy_from_slice(&block[offset..offset + size]);
+        self.pos += size as u64;
+        Ok(size)
+    }
+}
+
+impl<R: Read + Seek> Seek for ZisofsReader<R> {
+    fn seek(&mut self, whence: SeekFrom) -> Result<u64> {
+        self.pos = seek_position(self.pos, self.size, whence)?;
+        Ok(self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::io::Cursor;
+    use std::io::Read;
+    use std::io::Seek;
+    use std::io::SeekFrom;
+    use std::io::Write;
+
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+
+    use super::ZisofsReader;
+    use super::MAGIC;
+
+    /// Compress `data` with zisofs using 32 KiB blocks.
+    fn compress(data: &[u8]) -> Vec<u8> {
+        let blocks = data.chunks(1 << 15).collect::<Vec<_>>();
+        let mut header = MAGIC.to_vec();
+        header.extend(&[data.len() as u8, (data.len() >> 8) as u8, (data.len() >> 16) as u8, 0]);
+        header.extend(&[4, 15, 0, 0]);
+
+        let mut body = Vec::new();
+        let mut pointers = Vec::new();
+        let table_end = 16 + (blocks.len() + 1) * 4;
+        for block in blocks.iter() {
+            pointers.push(table_end + body.len());
+            if block.iter().any(|&b| b != 0) {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(block).unwrap();
+                body.extend(encoder.finish().unwrap());
+            }
+        }
+        pointers.push(table_end + body.len());
+
+        for pointer in pointers {
+            header.extend(&[pointer as u8, (pointer >> 8) as u8, (pointer >> 16) as u8, 0]);
+        }
+        header.extend(body);
+        header
+    }
+
+    #[test]
+    fn test_read() {
+        let mut data = b"Hello, World!\n".repeat(5000);
+        data.extend(vec![0; 40000]);
+        data.extend(b"end");
+
+        let mut reader = ZisofsReader::new(Cursor::new(compress(&data))).unwrap();
+        assert_eq!(reader.size(), data.len() as u64);
+
+        let mut decompressed = Vec::new();
+        reader.read_to_end(&mut decompressed).unwrap();
+        assert_eq!(decompressed, data);
+    }
+
+    #[test]
+    fn test_seek() {
+        let data = (0..100000u32).map(|x| (x % 251) as u8).collect::<Vec<u8>>();
+        let mut reader = ZisofsReader::new(Cursor::new(compress(&data))).unwrap();
+
+        let mut buffer = [0; 10];
+        reader.seek(SeekFrom::Start(70000)).unwrap();
+        reader.read_exact(&mut buffer).unwrap();
+        assert_eq!(&buffer, &data[70000..70010]);
+
+        reader.seek(SeekFrom::End(-5)).unwrap();
+        let mut end = Vec::new();
+        reader.read_to_end(&mut end).unwrap();
+        assert_eq!(end, &data[data.len() - 5..]);
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        assert!(ZisofsReader::new(Cursor::new(vec![0; 32])).is_err());
+
+        // a size of 4 GiB needs a larger block pointers table
+        let mut compressed = compress(b"data");
+        compressed[8..12].copy_from_slice(b"\xff\xff\xff\xff");
+        assert!(ZisofsReader::new(Cursor::new(compressed)).is_err());
+    }
+
+    #[test]
+    fn test_invalid_block() {
+        let data = b"Hello, World!\n".repeat(100);
+        let mut buffer = Vec::new();
+
+        // a block pointer past the end of the block
+        let mut compressed = compress(&data);
+        compressed[20..24].copy_from_slice(b"\xff\xff\xff\x00");
+        let mut reader = ZisofsReader::new(Cursor::new(compressed)).unwrap();
+        assert!(reader.read_to_end(&mut buffer).is_err());
+
+        // a block decompressing to less than the block size
+        let mut compressed = compress(&data);
+        compressed[8] += 1;
+        let mut reader = ZisofsReader::new(Cursor::new(compressed)).unwrap();
+        assert!(reader.read_to_end(&mut buffer).is_err());
+    }
+}
@@ -15,8 +15,11 @@
 
 extern crate btoi;
 extern crate chrono;
+extern crate crc32fast;
+extern crate encoding_rs;
 #[macro_use]
 extern crate error_chain;
+extern crate flate2;
 extern crate memchr;
 #[macro_use]
 extern crate nom;
@@ -25,6 +28,7 @@ extern crate nom;
 mod utils;
 
 pub mod error;
+pub mod fat;
 pub mod iso;
 
 pub use self::error::Result;
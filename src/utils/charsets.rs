@@ -0,0 +1,190 @@
+use std::borrow::Cow;
+use std::ffi::OsStr;
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
+
+use encoding_rs::SHIFT_JIS;
+
+/// The character set used to decode the identifiers of the primary hierarchy.
+///
+/// ISO-9660 only allows d-characters in identifiers, but many discs were
+/// mastered with names in the local encoding of the authoring system.
+/// Decoding never fails: bytes that cannot be decoded are replaced with
+/// `U+FFFD`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Charset {
+    /// UTF-8, as written by most modern mastering tools.
+    #[default]
+    Utf8,
+    /// ISO-8859-1, common on European discs.
+    Latin1,
+    /// The original IBM PC code page, used by DOS mastering tools.
+    Cp437,
+    /// Shift-JIS, common on Japanese discs.
+    ShiftJis,
+    /// Use UTF-8 if the identifier is valid UTF-8, then Shift-JIS if it is
+    /// valid Shift-JIS without single-byte katakana, and Latin-1 otherwise.
+    ///
+    /// This is only a guess: pairs of Latin-1 characters may still form
+    /// valid Shift-JIS kanji (e.g. `\xe9t` decodes to `騁`).
+    Auto,
+}
+
+impl Charset {
+    /// Decode `input` using this character set.
+    pub fn decode(&self, input: &[u8]) -> String {
+        match *self {
+            Charset::Utf8 => String::from_utf8_lossy(input).into_owned(),
+            Charset::Latin1 => input.iter().map(|&b| b as char).collect(),
+            Charset::Cp437 => input.iter().map(|&b| cp437(b)).collect(),
+            Charset::ShiftJis => SHIFT_JIS.decode_without_bom_handling(input).0.into_owned(),
+            Charset::Auto => match ::std::str::from_utf8(input) {
+                Ok(s) => s.to_owned(),
+                // upper-case Latin-1 letters are single-byte katakana
+                Err(_) => SHIFT_JIS
+                    .decode_without_bom_handling_and_without_replacement(input)
+                    .filter(|s| !s.chars().any(|c| ('\u{FF61}'..='\u{FF9F}').contains(&c)))
+                    .map(|s| s.into_owned())
+                    .unwrap_or_else(|| Charset::Latin1.decode(input)),
+            },
+        }
+    }
+}
+
+/// The characters of code page 437 above the ASCII range.
+const CP437_HIGH: [char; 128] = [
+    '\u{00C7}', '\u{00FC}', '\u{00E9}', '\u{00E2}', '\u{00E4}', '\u{00E0}', '\u{00E5}', '\u{00E7}',
+    '\u{00EA}', '\u{00EB}', '\u{00E8}', '\u{00EF}', '\u{00EE}', '\u{00EC}', '\u{00C4}', '\u{00C5}',
+    '\u{00C9}', '\u{00E6}', '\u{00C6}', '\u{00F4}', '\u{00F6}', '\u{00F2}', '\u{00FB}', '\u{00F9}',
+    '\u{00FF}', '\u{00D6}', '\u{00DC}', '\u{00A2}', '\u{00A3}', '\u{00A5}', '\u{20A7}', '\u{0192}',
+    '\u{00E1}', '\u{00ED}', '\u{00F3}', '\u{00FA}', '\u{00F1}', '\u{00D1}', '\u{00AA}', '\u{00BA}',
+    '\u{00BF}', '\u{2310}', '\u{00AC}', '\u{00BD}', '\u{00BC}', '\u{00A1}', '\u{00AB}', '\u{00BB}',
+    '\u{2591}', '\u{2592}', '\u{2593}', '\u{2502}', '\u{2524}', '\u{2561}', '\u{2562}', '\u{2556}',
+    '\u{2555}', '\u{2563}', '\u{2551}', '\u{2557}', '\u{255D}', '\u{255C}', '\u{255B}', '\u{2510}',
+    '\u{2514}', '\u{2534}', '\u{252C}', '\u{251C}', '\u{2500}', '\u{253C}', '\u{255E}', '\u{255F}',
+    '\u{255A}', '\u{2554}', '\u{2569}', '\u{2566}', '\u{2560}', '\u{2550}', '\u{256C}', '\u{2567}',
+    '\u{2568}', '\u{2564}', '\u{2565}', '\u{2559}', '\u{2558}', '\u{2552}', '\u{2553}', '\u{256B}',
+    '\u{256A}', '\u{2518}', '\u{250C}', '\u{2588}', '\u{2584}', '\u{258C}', '\u{2590}', '\u{2580}',
+    '\u{03B1}', '\u{00DF}', '\u{0393}', '\u{03C0}', '\u{03A3}', '\u{03C3}', '\u{00B5}', '\u{03C4}',
+    '\u{03A6}', '\u{0398}', '\u{03A9}', '\u{03B4}', '\u{221E}', '\u{03C6}', '\u{03B5}', '\u{2229}',
+    '\u{2261}', '\u{00B1}', '\u{2265}', '\u{2264}', '\u{2320}', '\u{2321}', '\u{00F7}', '\u{2248}',
+    '\u{00B0}', '\u{2219}', '\u{00B7}', '\u{221A}', '\u{207F}', '\u{00B2}', '\u{25A0}', '\u{00A0}',
+];
+
+/// Decode a single byte encoded in code page 437.
+fn cp437(byte: u8) -> char {
+    match byte {
+        0x00..=0x7F => byte as char,
+        _ => CP437_HIGH[byte as usize - 0x80],
+    }
+}
+
+/// Decode a string encoded in big-endian UCS-2.
+///
+/// Returns `None` when the input has an odd length or contains unpaired
+/// surrogates.
+pub fn ucs2_be(input: &[u8]) -> Option<String> {
+    if !input.len().is_multiple_of(2) {
+        return None;
+    }
+    let units = input
+        .chunks(2)
+        .map(|c| ((c[0] as u16) << 8) | c[1] as u16)
+        .collect::<Vec<u16>>();
+    String::from_utf16(&units).ok()
+}
+
+/// Decode a null-terminated string encoded in little-endian UTF-16.
+///
+/// Unpaired surrogates are replaced with `U+FFFD`.
+pub fn utf16_le(input: &[u8]) -> String {
+    let units = input
+        .chunks(2)
+        .filter(|c| c.len() == 2)
+        .map(|c| c[0] as u16 | (c[1] as u16) << 8)
+        .take_while(|&unit| unit != 0)
+        .collect::<Vec<u16>>();
+    String::from_utf16_lossy(&units)
+}
+
+/// Get the bytes of an OS string, which are only known on Unix.
+///
+/// On other platforms, the string is encoded in UTF-8 lossily.
+#[cfg(unix)]
+pub fn os_bytes(input: &OsStr) -> Cow<'_, [u8]> {
+    Cow::Borrowed(input.as_bytes())
+}
+
+/// Get the bytes of an OS string, which are only known on Unix.
+///
+/// On other platforms, the string is encoded in UTF-8 lossily.
+#[cfg(not(unix))]
+pub fn os_bytes(input: &OsStr) -> Cow<'_, [u8]> {
+    match input.to_string_lossy() {
+        Cow::Borrowed(s) => Cow::Borrowed(s.as_bytes()),
+        Cow::Owned(s) => Cow::Owned(s.into_bytes()),
+    }
+}
+
+/// Decode a string padded with spaces or null bytes, removing the padding.
+///
+/// Invalid UTF-8 sequences are replaced with `U+FFFD`.
+pub fn padded(input: &[u8]) -> String {
+    String::from_utf8_lossy(input)
+        .trim_end_matches(&[' ', '\0'][..])
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_ucs2_be() {
+        assert_eq!(ucs2_be(b"\0A\0P\0K"), Some(String::from("APK")));
+        assert_eq!(ucs2_be(b"\x00\xe9\x00"), None);
+        assert_eq!(ucs2_be(b"\x00\xe9\x00t\x00\xe9"), Some(String::from("été")));
+        assert_eq!(ucs2_be(b"\xd8\x00"), None);
+    }
+
+    #[test]
+    fn test_utf16_le() {
+        assert_eq!(utf16_le(b"E\0F\0I\0\0\0X\0"), "EFI");
+        assert_eq!(utf16_le(b"\xe9\0t\0\xe9\0"), "été");
+        assert_eq!(utf16_le(b"\0\xd8"), "\u{FFFD}");
+    }
+
+    #[test]
+    fn test_charsets() {
+        assert_eq!(Charset::Utf8.decode(b"\xc3\xa9t\xc3\xa9"), "été");
+        assert_eq!(Charset::Utf8.decode(b"\xe9t\xe9"), "\u{FFFD}t\u{FFFD}");
+        assert_eq!(Charset::Latin1.decode(b"\xe9t\xe9"), "été");
+        assert_eq!(Charset::Cp437.decode(b"\x82t\x82 \xe1"), "été ß");
+        assert_eq!(Charset::ShiftJis.decode(b"\x93\xfa\x96\x7b"), "日本");
+        assert_eq!(Charset::ShiftJis.decode(b"\x81"), "\u{FFFD}");
+    }
+
+    #[test]
+    fn test_charset_auto() {
+        assert_eq!(Charset::Auto.decode(b"APK"), "APK");
+        assert_eq!(Charset::Auto.decode(b"\xc3\xa9t\xc3\xa9"), "été");
+        assert_eq!(Charset::Auto.decode(b"\x93\xfa\x96\x7b"), "日本");
+        assert_eq!(Charset::Auto.decode(b"caf\xe9"), "café");
+        assert_eq!(Charset::Auto.decode(b"R\xc9SUM\xc9.TXT"), "RÉSUMÉ.TXT");
+        assert_eq!(Charset::Auto.decode(b"\xb1\xb2\xb3"), "±²³");
+    }
+
+    #[test]
+    fn test_charset_default() {
+        assert_eq!(Charset::default(), Charset::Utf8);
+        assert_eq!(Charset::default().decode(b"\xe9t\xe9s"), "\u{FFFD}t\u{FFFD}s");
+    }
+
+    #[test]
+    fn test_padded() {
+        assert_eq!(padded(b"LINUX   "), "LINUX");
+        assert_eq!(padded(b"EL TORITO\0\0"), "EL TORITO");
+        assert_eq!(padded(b"  "), "");
+    }
+}
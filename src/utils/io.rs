@@ -0,0 +1,38 @@
+use std::cmp::min;
+use std::io::Error;
+use std::io::ErrorKind::InvalidInput;
+use std::io::Result;
+use std::io::SeekFrom;
+
+/// Compute the new position of a cursor over `length` bytes after a seek.
+///
+/// Positions past the end are clamped to the end.
+pub fn seek_position(pos: u64, length: u64, whence: SeekFrom) -> Result<u64> {
+    let err = Error::new(InvalidInput, "invalid seek to a negative position");
+
+    Ok(match whence {
+        SeekFrom::Current(x) if (pos as i64 + x) < 0 => return Err(err),
+        SeekFrom::Current(x) => min((pos as i64 + x) as u64, length),
+        SeekFrom::End(x) if (length as i64 + x) < 0 => return Err(err),
+        SeekFrom::End(x) => min((length as i64 + x) as u64, length),
+        SeekFrom::Start(x) => min(x, length),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::io::SeekFrom;
+
+    use super::seek_position;
+
+    #[test]
+    fn test_seek_position() {
+        assert_eq!(seek_position(5, 10, SeekFrom::Current(2)).unwrap(), 7);
+        assert_eq!(seek_position(5, 10, SeekFrom::Current(20)).unwrap(), 10);
+        assert!(seek_position(5, 10, SeekFrom::Current(-6)).is_err());
+        assert_eq!(seek_position(5, 10, SeekFrom::End(-3)).unwrap(), 7);
+        assert!(seek_position(5, 10, SeekFrom::End(-11)).is_err());
+        assert_eq!(seek_position(5, 10, SeekFrom::Start(12)).unwrap(), 10);
+    }
+}
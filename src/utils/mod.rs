@@ -1,2 +1,4 @@
 #[macro_use]
 pub mod parsers;
+pub mod charsets;
+pub mod io;
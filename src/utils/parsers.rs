@@ -6,15 +6,15 @@ macro_rules! null_terminated (
     )
 );
 
-/// Parse an u8 only if it matches the given pattern.
+/// Parse an u8 only if it matches one of the given patterns.
 macro_rules! matching {
-    ($i: expr, $pattern: pat) => ({
+    ($i: expr, $($pattern: pat)|+) => ({
         use $crate::nom::Err::{Incomplete, Error};
         use $crate::nom::Context::Code;
         use $crate::nom::{ErrorKind, Needed};
         match $i.get(0) {
             None => Err(Incomplete(Needed::Size(1))),
-            Some(x @ &$pattern) => Ok((&$i[1..], *x)),
+            Some(&x) if matches!(x, $($pattern)|+) => Ok((&$i[1..], x)),
             _ => Err(Error(Code($i, ErrorKind::Custom(0)))),
         }
     })
@@ -63,8 +63,10 @@ mod tests {
         let buf1 = b"\x02";
         assert!(matching!(&buf1[..], _).is_ok());
         assert!(matching!(&buf1[..], 0).is_err());
-        assert!(matching!(&buf1[..], 1...5).is_ok());
-        assert!(matching!(&buf1[..], 1...2).is_ok());
+        assert!(matching!(&buf1[..], 1..=5).is_ok());
+        assert!(matching!(&buf1[..], 1..=2).is_ok());
+        assert!(matching!(&buf1[..], 1 | 2).is_ok());
+        assert!(matching!(&buf1[..], 0 | 3).is_err());
     }
 
     #[test]
@@ -0,0 +1,86 @@
+use std::io::Cursor;
+use std::io::Read;
+
+use opticaldisc::iso::Charset;
+use opticaldisc::iso::IsoFs;
+use opticaldisc::iso::Options;
+
+const SECTOR: usize = 2048;
+
+/// Build an ISO-9660 directory record with the given identifier and flags.
+fn record(id: &[u8], extent: u32, length: u32, flags: u8) -> Vec<u8> {
+    let mut raw = vec![33 + id.len() as u8 + id.len().is_multiple_of(2) as u8, 0];
+    raw.extend_from_slice(&extent.to_le_bytes());
+    raw.extend_from_slice(&extent.to_be_bytes());
+    raw.extend_from_slice(&length.to_le_bytes());
+    raw.extend_from_slice(&length.to_be_bytes());
+    raw.extend_from_slice(b"\x76\x05\x01\x0C\x00\x00\x00");
+    raw.extend_from_slice(&[flags, 0, 0, 1, 0, 0, 1, id.len() as u8]);
+    raw.extend_from_slice(id);
+    raw.resize(raw[0] as usize, 0);
+    raw
+}
+
+/// Build an image with a single file named `RÉSUMÉ.TXT` in Latin-1.
+fn image() -> Vec<u8> {
+    let mut image = vec![0; 20 * SECTOR];
+
+    // Primary Volume Descriptor
+    let pvd = &mut image[16 * SECTOR..17 * SECTOR];
+    pvd[..8].copy_from_slice(b"\x01CD001\x01\x00");
+    pvd[8..72].iter_mut().for_each(|b| *b = b' ');
+    pvd[80..88].copy_from_slice(b"\x14\0\0\0\0\0\0\x14");
+    pvd[120..132].copy_from_slice(b"\x01\0\0\x01\x01\0\0\x01\0\x08\x08\0");
+    pvd[156..190].copy_from_slice(&record(b"\0", 18, 2048, 0x02));
+    pvd[190..813].iter_mut().for_each(|b| *b = b' ');
+    for date in 0..4 {
+        let start = 813 + date * 17;
+        pvd[start..start + 16].iter_mut().for_each(|b| *b = b'0');
+    }
+    pvd[881] = 1;
+
+    // Volume Descriptor Set Terminator
+    image[17 * SECTOR..17 * SECTOR + 7].copy_from_slice(b"\xFFCD001\x01");
+
+    let root = [
+        record(b"\0", 18, 2048, 0x02),
+        record(b"\x01", 18, 2048, 0x02),
+        record(b"R\xc9SUM\xc9.TXT;1", 19, 6, 0x00),
+    ]
+    .concat();
+    image[18 * SECTOR..18 * SECTOR + root.len()].copy_from_slice(&root);
+    image[19 * SECTOR..19 * SECTOR + 6].copy_from_slice(b"hello\n");
+
+    image
+}
+
+#[test]
+fn test_latin1() {
+    let options = Options::new().charset(Charset::Latin1);
+    let mut iso = IsoFs::with_options(Cursor::new(image()), options).unwrap();
+    let metadata = iso.metadata("/RÉSUMÉ.TXT").unwrap();
+    assert_eq!(metadata.name(), "RÉSUMÉ.TXT");
+    assert_eq!(metadata.identifier(), b"R\xc9SUM\xc9.TXT;1");
+}
+
+#[test]
+#[cfg(unix)]
+fn test_undecoded_names() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    let mut iso = IsoFs::from_buffer(image()).unwrap();
+    let entries = iso.read_dir("/").unwrap();
+    assert_eq!(entries[0].name(), "R\u{FFFD}SUM\u{FFFD}.TXT");
+    assert_eq!(entries[0].file_name().as_bytes(), b"R\xc9SUM\xc9.TXT");
+
+    // paths that are not valid UTF-8 are compared to the raw identifiers
+    let path = Path::new("/").join(entries[0].file_name());
+    let mut content = String::new();
+    iso.open_file(&path).unwrap().read_to_string(&mut content).unwrap();
+    assert_eq!(content, "hello\n");
+    assert!(iso.is_file(Path::new(OsStr::from_bytes(b"/R\xc9SUM\xc9.TXT;1"))));
+    assert!(!iso.exists(Path::new(OsStr::from_bytes(b"/R\xc9SUM\xc9.TXT;2"))));
+    assert!(!iso.exists(Path::new(OsStr::from_bytes(b"/R\xc9SUME.TXT"))));
+}
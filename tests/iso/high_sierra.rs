@@ -0,0 +1,161 @@
+use std::io::Read;
+
+use opticaldisc::iso::IsoFs;
+use opticaldisc::iso::VolumeDescriptor;
+
+const SECTOR: usize = 2048;
+
+/// Build a High Sierra directory record with the given identifier and flags.
+fn record(id: &[u8], extent: u32, length: u32, flags: u8) -> Vec<u8> {
+    let mut raw = vec![33 + id.len() as u8 + id.len().is_multiple_of(2) as u8, 0];
+    raw.extend_from_slice(&extent.to_le_bytes());
+    raw.extend_from_slice(&extent.to_be_bytes());
+    raw.extend_from_slice(&length.to_le_bytes());
+    raw.extend_from_slice(&length.to_be_bytes());
+    raw.extend_from_slice(b"\x56\x05\x01\x0C\x00\x00");
+    raw.extend_from_slice(&[flags, 0, 0, 0, 1, 0, 0, 1, id.len() as u8]);
+    raw.extend_from_slice(id);
+    raw.resize(raw[0] as usize, 0);
+    raw
+}
+
+/// Write directory records at the start of the given sector.
+fn directory(image: &mut [u8], sector: usize, records: &[Vec<u8>]) {
+    let contents = records.concat();
+    image[sector * SECTOR..sector * SECTOR + contents.len()].copy_from_slice(&contents);
+}
+
+/// Build a High Sierra image with a `DIR` directory and two files.
+fn image() -> Vec<u8> {
+    let mut image = vec![0; 24 * SECTOR];
+
+    // Standard File Structure Volume Descriptor
+    let pvd = &mut image[16 * SECTOR..17 * SECTOR];
+    pvd[..8].copy_from_slice(b"\x10\0\0\0\0\0\0\x10");
+    pvd[8..15].copy_from_slice(b"\x01CDROM\x01");
+    pvd[16..854].iter_mut().for_each(|b| *b = b' ');
+    pvd[16..18].copy_from_slice(b"HS");
+    pvd[48..53].copy_from_slice(b"HSVOL");
+    pvd[80..180].iter_mut().for_each(|b| *b = 0);
+    pvd[88..96].copy_from_slice(b"\x18\0\0\0\0\0\0\x18");
+    pvd[128..140].copy_from_slice(b"\x01\0\0\x01\x01\0\0\x01\0\x08\x08\0");
+    pvd[140..148].copy_from_slice(b"\x16\0\0\0\0\0\0\x16");
+    pvd[148..152].copy_from_slice(b"\x15\0\0\0");
+    pvd[164..168].copy_from_slice(b"\0\0\0\x16");
+    pvd[180..214].copy_from_slice(&record(b"\0", 18, 2048, 0x02));
+    pvd[790..806].copy_from_slice(b"1986050112000000");
+    pvd[806..854].iter_mut().for_each(|b| *b = b'0');
+    pvd[854] = 1;
+
+    // Volume Descriptor Set Terminator
+    image[17 * SECTOR + 8..17 * SECTOR + 15].copy_from_slice(b"\xFFCDROM\x01");
+
+    let root = [
+        record(b"\0", 18, 2048, 0x02),
+        record(b"\x01", 18, 2048, 0x02),
+        record(b"DIR", 19, 2048, 0x02),
+        record(b"README.TXT;1", 20, 6, 0x00),
+    ];
+    directory(&mut image, 18, &root);
+    let dir = [
+        record(b"\0", 19, 2048, 0x02),
+        record(b"\x01", 18, 2048, 0x02),
+        record(b"FILE.TXT;1", 20, 6, 0x00),
+    ];
+    directory(&mut image, 19, &dir);
+    image[20 * SECTOR..20 * SECTOR + 6].copy_from_slice(b"hello\n");
+
+    // Path tables
+    let l = b"\x12\0\0\0\0\x01\x01\0\0\0\x13\0\0\0\0\x03\x01\0DIR\0";
+    image[21 * SECTOR..21 * SECTOR + l.len()].copy_from_slice(l);
+    let m = b"\0\0\0\x12\0\x01\0\x01\0\0\0\0\0\x13\0\x03\0\x01DIR\0";
+    image[22 * SECTOR..22 * SECTOR + m.len()].copy_from_slice(m);
+
+    image
+}
+
+#[test]
+fn test_descriptors() {
+    let iso = IsoFs::from_buffer(image()).unwrap();
+    let info = iso.volume_info();
+    assert_eq!(info.system_id, "HS");
+    assert_eq!(info.volume_id, "HSVOL");
+    assert_eq!(info.volume_space_size, 24);
+    assert_eq!(info.bibliographic_file_id, "");
+    assert!(info.creation_date.is_some());
+    assert_eq!(info.modification_date, None);
+
+    let descriptors = iso.descriptors().collect::<Vec<_>>();
+    assert_eq!(descriptors.len(), 2);
+    match descriptors[0] {
+        (16, VolumeDescriptor::Primary(pvd)) => assert!(pvd.is_high_sierra()),
+        ref other => panic!("unexpected descriptor: {:?}", other),
+    }
+}
+
+#[test]
+fn test_read_dir() {
+    let mut iso = IsoFs::from_buffer(image()).unwrap();
+    let names = iso
+        .read_dir("/")
+        .unwrap()
+        .iter()
+        .map(|entry| entry.name().to_owned())
+        .collect::<Vec<String>>();
+    assert_eq!(names, vec!["DIR", "README.TXT"]);
+    assert!(iso.is_dir("/DIR"));
+    assert!(iso.is_file("/DIR/FILE.TXT"));
+}
+
+#[test]
+fn test_metadata() {
+    use chrono::Datelike;
+
+    let mut iso = IsoFs::from_buffer(image()).unwrap();
+    let metadata = iso.metadata("/README.TXT").unwrap();
+    assert_eq!(metadata.len(), 6);
+    assert_eq!(metadata.version(), Some(1));
+    let date = metadata.recording_date().unwrap();
+    assert_eq!((date.year(), date.month(), date.day()), (1986, 5, 1));
+}
+
+#[test]
+fn test_open_file() {
+    let mut iso = IsoFs::from_buffer(image()).unwrap();
+    let mut content = String::new();
+    iso.open_file("/DIR/FILE.TXT")
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, "hello\n");
+}
+
+#[test]
+fn test_path_tables() {
+    use opticaldisc::iso::Options;
+
+    let mut iso = IsoFs::from_buffer(image()).unwrap();
+    let table = iso.check_path_tables().unwrap();
+    assert_eq!(table.entries().len(), 2);
+    assert_eq!(table.entries()[1].name, "DIR");
+
+    let options = Options::new().path_table_lookup(true);
+    let mut iso = IsoFs::with_options(::std::io::Cursor::new(image()), options).unwrap();
+    assert!(iso.is_file("/DIR/FILE.TXT"));
+}
+
+#[test]
+fn test_sessions() {
+    // Append a second session at sector 24 reusing the tree of the first
+    let mut image = image();
+    let set = image[16 * SECTOR..18 * SECTOR].to_vec();
+    image.resize(42 * SECTOR, 0);
+    image[40 * SECTOR..].copy_from_slice(&set);
+    image[40 * SECTOR + 48..40 * SECTOR + 53].copy_from_slice(b"HSTWO");
+
+    let mut iso = IsoFs::from_buffer(image).unwrap();
+    let starts = iso.sessions().iter().map(|s| s.start).collect::<Vec<u32>>();
+    assert_eq!(starts, vec![0, 24]);
+    assert_eq!(iso.volume_info().volume_id, "HSTWO");
+    assert!(iso.is_file("/DIR/FILE.TXT"));
+}
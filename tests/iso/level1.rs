@@ -1,4 +1,7 @@
 use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::Path;
 use std::path::PathBuf;
 
 use opticaldisc::iso::IsoFs;
@@ -41,3 +44,493 @@ fn test_metadata() {
     assert!(arch.is_file());
     assert!(!arch.is_dir());
 }
+
+#[test]
+fn test_open_file() {
+    let mut iso = IsoFs::from_path(path.as_path()).unwrap();
+    let mut file = iso.open_file("/ETC/APK/ARCH").unwrap();
+    let mut content = String::new();
+    file.seek(SeekFrom::Start(3)).unwrap();
+    file.read_to_string(&mut content).unwrap();
+    assert_eq!(content, "_64\n");
+}
+
+#[test]
+fn test_no_boot_catalog() {
+    let mut iso = IsoFs::from_path(path.as_path()).unwrap();
+    assert!(iso.boot_catalog().is_err());
+    assert!(iso.open_efi_partition().is_err());
+}
+
+#[test]
+fn test_boot_catalog() {
+    use opticaldisc::iso::Emulation;
+    use opticaldisc::iso::Platform;
+
+    // Turn the terminator into a boot record pointing to a catalog stored
+    // in the system area, with two boot images following it
+    let mut image = ::std::fs::read(path.as_path()).unwrap();
+    let record = 17 * 2048;
+    image[record..record + 2048].iter_mut().for_each(|b| *b = 0);
+    image[record..record + 7].copy_from_slice(b"\x00CD001\x01");
+    image[record + 7..record + 30].copy_from_slice(b"EL TORITO SPECIFICATION");
+    image[record + 0x47..record + 0x4B].copy_from_slice(b"\x01\x00\x00\x00");
+    image[18 * 2048..18 * 2048 + 7].copy_from_slice(b"\xFFCD001\x01");
+
+    let catalog = 2048;
+    image[catalog..catalog + 2].copy_from_slice(b"\x01\x00");
+    image[catalog + 4..catalog + 10].copy_from_slice(b"VENDOR");
+    image[catalog + 30..catalog + 32].copy_from_slice(b"\x55\xAA");
+    let sum = image[catalog..catalog + 32]
+        .chunks(2)
+        .fold(0u16, |acc, w| acc.wrapping_add(w[0] as u16 | (w[1] as u16) << 8));
+    let checksum = 0u16.wrapping_sub(sum);
+    image[catalog + 28..catalog + 30].copy_from_slice(&checksum.to_le_bytes());
+    image[catalog + 32..catalog + 44].copy_from_slice(b"\x88\x00\x00\x00\x00\x00\x04\x00\x02\x00\x00\x00");
+    image[catalog + 64..catalog + 68].copy_from_slice(b"\x91\xEF\x01\x00");
+    image[catalog + 96..catalog + 108].copy_from_slice(b"\x88\x00\x00\x00\x00\x00\x01\x00\x03\x00\x00\x00");
+
+    image[2 * 2048..3 * 2048].iter_mut().for_each(|b| *b = 0xAA);
+    image[3 * 2048..3 * 2048 + 512].iter_mut().for_each(|b| *b = 0xEF);
+
+    let mut iso = IsoFs::from_buffer(image.as_slice()).unwrap();
+    let catalog = iso.boot_catalog().unwrap();
+    assert_eq!(catalog.validation.platform, Platform::X86);
+    assert_eq!(catalog.validation.id, "VENDOR");
+    assert_eq!(catalog.default.emulation, Emulation::NoEmulation);
+    assert_eq!(catalog.default.load_rba, 2);
+    assert_eq!(catalog.sections.len(), 1);
+    assert_eq!(catalog.sections[0].platform, Platform::Efi);
+
+    let entries = catalog
+        .entries()
+        .into_iter()
+        .map(|(_, entry)| entry.clone())
+        .collect::<Vec<_>>();
+    let mut contents = Vec::new();
+    iso.open_boot_image(&entries[0])
+        .unwrap()
+        .read_to_end(&mut contents)
+        .unwrap();
+    assert_eq!(contents, vec![0xAA; 2048]);
+
+    contents.clear();
+    iso.open_boot_image(&entries[1])
+        .unwrap()
+        .read_to_end(&mut contents)
+        .unwrap();
+    assert_eq!(contents, vec![0xEF; 512]);
+}
+
+#[test]
+fn test_overlapping_files() {
+    use opticaldisc::iso::Partition;
+    use opticaldisc::iso::Scheme;
+
+    let mut iso = IsoFs::from_path(path.as_path()).unwrap();
+    let mut partition = Partition {
+        scheme: Scheme::Mbr,
+        index: 1,
+        offset: 0,
+        length: 16 * 2048,
+    };
+    assert!(iso.overlapping_files(&partition).unwrap().is_empty());
+
+    partition.length = ::std::fs::metadata(path.as_path()).unwrap().len();
+    let files = iso.overlapping_files(&partition).unwrap();
+    assert!(files.iter().any(|f| f.path() == Path::new("/ETC/APK/ARCH")));
+}
+
+#[test]
+fn test_path_tables() {
+    use opticaldisc::iso::ByteOrder;
+
+    let mut iso = IsoFs::from_path(path.as_path()).unwrap();
+    let table = iso.check_path_tables().unwrap();
+    assert_eq!(table, iso.path_table(ByteOrder::BigEndian).unwrap());
+    let number = table.find("/ETC/APK").unwrap();
+    assert_eq!(table.path(number).unwrap(), ::std::path::Path::new("/ETC/APK"));
+    let subdirs = iso.read_dir("/ETC/APK").unwrap().iter().filter(|m| m.is_dir()).count();
+    assert_eq!(table.entries().iter().filter(|e| e.parent == number).count(), subdirs);
+}
+
+#[test]
+fn test_path_table_lookup() {
+    use opticaldisc::iso::Options;
+
+    let file = ::std::fs::File::open(path.as_path()).unwrap();
+    let options = Options::new().rock_ridge(false).path_table_lookup(true);
+    let mut iso = IsoFs::with_options(file, options).unwrap();
+    assert!(iso.is_dir("/ETC/APK"));
+    assert!(iso.is_file("/ETC/APK/ARCH"));
+    assert!(!iso.exists("/ETC/APK/NOFILE"));
+    assert_eq!(iso.metadata("ETC/APK").unwrap().path(), ::std::path::Path::new("/ETC/APK"));
+
+    let mut content = String::new();
+    iso.open_file("/ETC/APK/ARCH").unwrap().read_to_string(&mut content).unwrap();
+    assert_eq!(content, "x86_64\n");
+}
+
+#[test]
+fn test_path_table_lookup_policy() {
+    use opticaldisc::iso::LookupPolicy;
+    use opticaldisc::iso::Options;
+
+    // path tables resolve names the same way as the directory hierarchy
+    for &path_tables in [false, true].iter() {
+        let file = ::std::fs::File::open(path.as_path()).unwrap();
+        let options = Options::new()
+            .rock_ridge(false)
+            .path_table_lookup(path_tables)
+            .lookup(LookupPolicy::relaxed());
+        let mut iso = IsoFs::with_options(file, options).unwrap();
+        assert!(iso.is_dir("etc/apk"));
+        assert!(iso.is_file("etc/apk/arch"));
+        assert!(!iso.exists("etc/apk;1/arch"));
+        assert_eq!(iso.metadata("etc/apk").unwrap().path(), Path::new("/ETC/APK"));
+        assert_eq!(iso.metadata("etc/apk/arch").unwrap().path(), Path::new("/ETC/APK/ARCH"));
+    }
+}
+
+#[test]
+fn test_extended_attributes() {
+    let mut iso = IsoFs::from_path(path.as_path()).unwrap();
+    assert!(!iso.metadata("/ETC/APK/ARCH").unwrap().has_extended_attributes());
+    assert_eq!(iso.extended_attributes("/ETC/APK/ARCH").unwrap(), None);
+}
+
+#[test]
+fn test_file_flags() {
+    let mut iso = IsoFs::from_path(path.as_path()).unwrap();
+    let arch = iso.metadata("/ETC/APK/ARCH").unwrap();
+    assert!(!arch.is_hidden());
+    assert!(!arch.is_associated());
+    assert!(!arch.has_record_format());
+    assert!(!arch.is_protected());
+    assert!(!arch.is_multi_extent());
+    assert!(arch.associated_file().is_none());
+}
+
+#[test]
+fn test_versioned_lookup() {
+    let mut iso = IsoFs::from_path(path.as_path()).unwrap();
+    assert!(iso.is_file("/ETC/APK/ARCH;1"));
+    assert!(iso.is_file("/ETC/APK/ARCH.;1"));
+    assert!(!iso.exists("/ETC/APK/ARCH;2"));
+    assert_eq!(iso.metadata("/ETC/APK/ARCH").unwrap().version(), Some(1));
+    assert!(iso.versions("/ETC/APK/NOFILE").is_err());
+
+    let mut content = String::new();
+    iso.open_file("/ETC/APK/ARCH;1").unwrap().read_to_string(&mut content).unwrap();
+    assert_eq!(content, "x86_64\n");
+}
+
+#[test]
+fn test_lookup_policy() {
+    use opticaldisc::iso::LookupPolicy;
+    use opticaldisc::iso::Options;
+
+    let mut iso = IsoFs::from_path(path.as_path()).unwrap();
+    assert_eq!(iso.lookup_policy(), LookupPolicy::exact());
+    assert!(!iso.exists("etc/apk/arch"));
+
+    iso.set_lookup_policy(LookupPolicy::exact().case_insensitive(true));
+    assert!(iso.is_file("etc/apk/arch"));
+    assert!(iso.is_file("etc/apk/arch;1"));
+    assert!(!iso.exists("etc/apk./arch"));
+
+    let options = Options::new().lookup(LookupPolicy::relaxed());
+    let file = ::std::fs::File::open(path.as_path()).unwrap();
+    let mut iso = IsoFs::with_options(file, options).unwrap();
+    assert!(iso.is_file("etc/apk./arch.;1"));
+    assert_eq!(iso.metadata("etc/apk/arch").unwrap().path(), Path::new("/ETC/APK/ARCH"));
+}
+
+#[test]
+fn test_identifiers() {
+    let mut iso = IsoFs::from_path(path.as_path()).unwrap();
+    let metadata = iso.metadata("/ETC/APK/ARCH").unwrap();
+    assert_eq!(metadata.identifier(), b"ARCH.;1");
+    assert_eq!(metadata.name(), "ARCH");
+
+    #[cfg(unix)]
+    {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+        assert!(!iso.exists(Path::new(OsStr::from_bytes(b"/ETC/\xff"))));
+    }
+}
+
+#[test]
+fn test_volume_info() {
+    use chrono::Datelike;
+    use chrono::Timelike;
+
+    let iso = IsoFs::from_path(path.as_path()).unwrap();
+    let info = iso.volume_info();
+    assert_eq!(info.system_id, "LINUX");
+    assert_eq!(info.volume_id, "CDROM");
+    assert_eq!(info.volume_space_size, 499);
+    assert_eq!(info.volume_set_size, 1);
+    assert_eq!(info.volume_sequence_number, 1);
+    assert_eq!(info.logical_block_size, 2048);
+    assert_eq!(info.volume_set_id, "");
+    assert_eq!(info.publisher_id, "");
+    assert!(info.application_id.starts_with("MKISOFS ISO9660/HFS/UDF FILESYSTEM BUILDER"));
+    assert_eq!(info.copyright_file_id, "");
+
+    let created = info.creation_date.unwrap();
+    assert_eq!((created.year(), created.month(), created.day()), (2018, 4, 26));
+    assert_eq!((created.hour(), created.minute(), created.second()), (17, 53, 8));
+    assert_eq!(created.offset().local_minus_utc(), 2 * 3600);
+    assert_eq!(info.modification_date, Some(created));
+    assert_eq!(info.expiration_date, None);
+    assert_eq!(info.effective_date, Some(created));
+
+    let mut iso = IsoFs::from_path(path.as_path()).unwrap();
+    let recorded = iso.metadata("/ETC/APK/ARCH").unwrap().recording_date().unwrap();
+    assert_eq!((recorded.year(), recorded.month(), recorded.day()), (2017, 11, 30));
+    assert_eq!(recorded.offset().local_minus_utc(), 3600);
+}
+
+#[test]
+fn test_descriptors() {
+    use opticaldisc::iso::VolumeDescriptor;
+
+    let iso = IsoFs::from_path(path.as_path()).unwrap();
+    let descriptors = iso.descriptors().collect::<Vec<_>>();
+    assert_eq!(descriptors.len(), 2);
+
+    match descriptors[0] {
+        (16, VolumeDescriptor::Primary(pvd)) => assert_eq!(&pvd.info, iso.volume_info()),
+        ref other => panic!("unexpected descriptor: {:?}", other),
+    }
+    match descriptors[1] {
+        (17, VolumeDescriptor::Terminator(term)) => assert_eq!(term.version(), 1),
+        ref other => panic!("unexpected descriptor: {:?}", other),
+    }
+}
+
+#[test]
+fn test_enhanced_volume_descriptor() {
+    use opticaldisc::iso::Options;
+    use opticaldisc::iso::Tree;
+    use opticaldisc::iso::VolumeDescriptor;
+
+    // Turn the terminator into an EVD sharing the root of the PVD
+    let mut image = ::std::fs::read(path.as_path()).unwrap();
+    let (pvd, evd) = (16 * 2048, 17 * 2048);
+    image.copy_within(pvd..pvd + 2048, evd);
+    image[evd] = 2;
+    image[evd + 6] = 2;
+    image[evd + 881] = 2;
+    image[evd + 2048..evd + 4096].iter_mut().for_each(|b| *b = 0);
+    image[evd + 2048..evd + 2055].copy_from_slice(b"\xFFCD001\x01");
+
+    let mut iso = IsoFs::from_buffer(image.as_slice()).unwrap();
+    assert!(iso.is_file("/ETC/APK/ARCH"));
+    match iso.descriptors().nth(1) {
+        Some((17, VolumeDescriptor::Enhanced(evd))) => assert!(evd.is_enhanced()),
+        other => panic!("unexpected descriptor: {:?}", other),
+    }
+
+    let options = Options::new().tree(Tree::Enhanced);
+    let mut iso = IsoFs::with_options(::std::io::Cursor::new(image), options).unwrap();
+    assert!(iso.is_file("/ETC/APK/ARCH.;1"));
+    assert!(!iso.exists("/ETC/APK/ARCH"));
+    assert_eq!(iso.metadata("/ETC/APK/ARCH.;1").unwrap().version(), None);
+
+    let file = ::std::fs::File::open(path.as_path()).unwrap();
+    assert!(IsoFs::with_options(file, Options::new().tree(Tree::Enhanced)).is_err());
+}
+
+#[test]
+fn test_volume_partitions() {
+    // Turn the terminator into a VPD spanning the sectors of the PVD and VPD
+    let mut image = ::std::fs::read(path.as_path()).unwrap();
+    let vpd = 17 * 2048;
+    image[vpd..vpd + 4096].iter_mut().for_each(|b| *b = 0);
+    image[vpd..vpd + 8].copy_from_slice(b"\x03CD001\x01\x00");
+    image[vpd + 8..vpd + 13].copy_from_slice(b"LINUX");
+    image[vpd + 40..vpd + 47].copy_from_slice(b"VENDOR ");
+    image[vpd + 72..vpd + 88].copy_from_slice(b"\x10\0\0\0\0\0\0\x10\x02\0\0\0\0\0\0\x02");
+    image[vpd + 2048..vpd + 2055].copy_from_slice(b"\xFFCD001\x01");
+
+    let mut iso = IsoFs::from_buffer(image.as_slice()).unwrap();
+    let partitions = iso.volume_partitions();
+    assert_eq!(partitions.len(), 1);
+    assert_eq!(partitions[0].system_id, "LINUX");
+    assert_eq!(partitions[0].partition_id, "VENDOR");
+    assert_eq!((partitions[0].location, partitions[0].size), (16, 2));
+
+    let mut contents = Vec::new();
+    iso.open_volume_partition(&partitions[0])
+        .unwrap()
+        .read_to_end(&mut contents)
+        .unwrap();
+    assert_eq!(contents.len(), 4096);
+    assert_eq!(&contents[..6], b"\x01CD001");
+    assert_eq!(&contents[2048..2054], b"\x03CD001");
+}
+
+#[test]
+fn test_read_dir_order() {
+    let mut iso = IsoFs::from_path(path.as_path()).unwrap();
+    let streamed = iso
+        .read_dir_iter("/ETC")
+        .unwrap()
+        .map(|entry| entry.unwrap().name().to_owned())
+        .collect::<Vec<String>>();
+    let cached = iso
+        .read_dir("/ETC")
+        .unwrap()
+        .iter()
+        .map(|entry| entry.name().to_owned())
+        .collect::<Vec<String>>();
+    assert_eq!(streamed, cached);
+
+    let mut sorted = cached.clone();
+    sorted.sort();
+    assert_eq!(cached, sorted);
+
+    assert!(iso.read_dir_iter("/ETC/APK/ARCH").is_err());
+}
+
+#[test]
+fn test_sessions() {
+    use opticaldisc::error::ErrorKind;
+    use opticaldisc::iso::Options;
+
+    // Append a second session at sector 520 reusing the tree of the first
+    let mut image = ::std::fs::read(path.as_path()).unwrap();
+    let start = 520 * 2048;
+    let set = image[16 * 2048..18 * 2048].to_vec();
+    image.resize(start + 18 * 2048, 0);
+    image[start + 16 * 2048..].copy_from_slice(&set);
+    let pvd = start + 16 * 2048;
+    image[pvd + 40..pvd + 48].copy_from_slice(b"SESSION2");
+    image[pvd + 80..pvd + 88].copy_from_slice(b"\x1a\x02\0\0\0\0\x02\x1a");
+
+    let mut iso = IsoFs::from_buffer(image.as_slice()).unwrap();
+    let starts = iso.sessions().iter().map(|s| s.start).collect::<Vec<u32>>();
+    assert_eq!(starts, vec![0, 520]);
+    assert_eq!(iso.current_session(), 1);
+    assert_eq!(iso.volume_info().volume_id, "SESSION2");
+    assert_eq!(iso.descriptors().next().unwrap().0, 536);
+    assert!(iso.is_file("/ETC/APK/ARCH"));
+
+    let options = Options::new().session(0);
+    let mut iso = IsoFs::with_options(::std::io::Cursor::new(image.as_slice()), options).unwrap();
+    assert_eq!(iso.volume_info().volume_id, "CDROM");
+    assert!(iso.is_file("/ETC/APK/ARCH"));
+
+    let options = Options::new().session_start(520);
+    let iso = IsoFs::with_options(::std::io::Cursor::new(image.as_slice()), options).unwrap();
+    assert_eq!(iso.sessions().len(), 1);
+    assert_eq!(iso.volume_info().volume_id, "SESSION2");
+
+    // sessions without descriptors keep the indices of the table of contents
+    let options = Options::new().toc(vec![0, 300, 520]);
+    let iso = IsoFs::with_options(::std::io::Cursor::new(image.as_slice()), options).unwrap();
+    let infos = iso.sessions().iter().map(|s| s.info.is_some()).collect::<Vec<bool>>();
+    assert_eq!(infos, vec![true, false, true]);
+    assert_eq!(iso.current_session(), 2);
+    assert_eq!(iso.volume_info().volume_id, "SESSION2");
+
+    let options = Options::new().toc(vec![0, 300, 520]).session(1);
+    match IsoFs::with_options(::std::io::Cursor::new(image.as_slice()), options) {
+        Err(err) => assert_eq!(err.to_string(), ErrorKind::NoPrimaryVolumeDescriptor.to_string()),
+        Ok(_) => panic!("expected an error"),
+    }
+    let options = Options::new().toc(vec![0, 300, 520]).session(3);
+    match IsoFs::with_options(::std::io::Cursor::new(image.as_slice()), options) {
+        Err(err) => assert_eq!(err.to_string(), ErrorKind::SessionNotFound(3).to_string()),
+        Ok(_) => panic!("expected an error"),
+    }
+
+    // a stray descriptor at the end of the image is not a session
+    let mut stray = image.clone();
+    stray.resize(560 * 2048, 0);
+    stray.extend_from_slice(&image[16 * 2048..17 * 2048]);
+    let iso = IsoFs::from_buffer(stray.as_slice()).unwrap();
+    let starts = iso.sessions().iter().map(|s| s.start).collect::<Vec<u32>>();
+    assert_eq!(starts, vec![0, 520]);
+    assert_eq!(iso.volume_info().volume_id, "SESSION2");
+
+    // I/O errors are not mistaken for a missing volume descriptor set
+    let options = Options::new().toc(vec![0, 1000]);
+    match IsoFs::with_options(::std::io::Cursor::new(image.as_slice()), options) {
+        Err(opticaldisc::error::Error(ErrorKind::Io(err), _)) => {
+            assert_eq!(err.kind(), ::std::io::ErrorKind::UnexpectedEof)
+        }
+        Err(err) => panic!("expected an I/O error, got {}", err),
+        Ok(_) => panic!("expected an error"),
+    }
+}
+
+#[test]
+fn test_volume_set() {
+    use std::io::Cursor;
+
+    use opticaldisc::error::ErrorKind;
+    use opticaldisc::iso::IsoVolumeSet;
+
+    // Make a volume of a two-volume set with the given sequence number
+    let volume = |set_id: &[u8], sequence_number: u8| {
+        let mut image = ::std::fs::read(path.as_path()).unwrap();
+        let pvd = 16 * 2048;
+        image[pvd + 120..pvd + 124].copy_from_slice(b"\x02\0\0\x02");
+        image[pvd + 124..pvd + 128].copy_from_slice(&[sequence_number, 0, 0, sequence_number]);
+        image[pvd + 190..pvd + 190 + set_id.len()].copy_from_slice(set_id);
+        image
+    };
+
+    // On the second volume, record ARCH on the first volume and erase its
+    // contents from the second one
+    let first = volume(b"ALPINE", 1);
+    let mut second = volume(b"ALPINE", 2);
+    let record = second.windows(8).position(|w| w == b"\x07ARCH.;1").unwrap() - 32;
+    second[record + 28..record + 32].copy_from_slice(b"\x01\0\0\x01");
+    let extent = second[record + 2..record + 6]
+        .iter()
+        .rev()
+        .fold(0, |acc, &b| (acc << 8) | b as usize);
+    second[extent * 2048..extent * 2048 + 7].iter_mut().for_each(|b| *b = 0);
+
+    let mut iso = IsoFs::from_buffer(second.clone()).unwrap();
+    assert_eq!(iso.metadata("/ETC/APK/ARCH").unwrap().volume_sequence_number(), 1);
+    match iso.open_file("/ETC/APK/ARCH") {
+        Err(err) => assert_eq!(err.to_string(), ErrorKind::VolumeNotFound(1).to_string()),
+        Ok(_) => panic!("expected an error"),
+    }
+    let metadata = iso.metadata("/ETC/APK/ARCH").unwrap();
+    match metadata.open_file(&mut iso) {
+        Err(err) => assert_eq!(err.to_string(), ErrorKind::VolumeNotFound(1).to_string()),
+        Ok(_) => panic!("expected an error"),
+    }
+
+    let volumes = vec![
+        IsoFs::new(Cursor::new(second.clone())).unwrap(),
+        IsoFs::new(Cursor::new(first)).unwrap(),
+    ];
+    let mut set = IsoVolumeSet::new(volumes).unwrap();
+    assert_eq!(set.volume_set_id(), "ALPINE");
+    assert!(set.is_complete());
+    let mut content = String::new();
+    set.open_file("/ETC/APK/ARCH")
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, "x86_64\n");
+
+    let volumes = vec![IsoFs::new(Cursor::new(second.clone())).unwrap()];
+    let mut set = IsoVolumeSet::new(volumes).unwrap();
+    assert!(!set.is_complete());
+    assert!(set.open_file("/ETC/APK/ARCH").is_err());
+
+    let volumes = vec![
+        IsoFs::new(Cursor::new(second)).unwrap(),
+        IsoFs::new(Cursor::new(volume(b"OTHER", 1))).unwrap(),
+    ];
+    assert!(IsoVolumeSet::new(volumes).is_err());
+}
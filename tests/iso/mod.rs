@@ -1 +1,4 @@
+mod charsets;
+mod high_sierra;
 mod level1;
+mod rockridge;
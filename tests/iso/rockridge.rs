@@ -0,0 +1,133 @@
+use std::io::Read;
+use std::path::Path;
+
+use opticaldisc::iso::IsoFs;
+
+const SECTOR: usize = 2048;
+
+/// Build an ISO-9660 directory record with the given identifier, flags and
+/// system use area.
+fn record(id: &[u8], extent: u32, length: u32, flags: u8, system_use: &[u8]) -> Vec<u8> {
+    let mut raw = vec![0, 0];
+    raw.extend_from_slice(&extent.to_le_bytes());
+    raw.extend_from_slice(&extent.to_be_bytes());
+    raw.extend_from_slice(&length.to_le_bytes());
+    raw.extend_from_slice(&length.to_be_bytes());
+    raw.extend_from_slice(b"\x76\x05\x01\x0C\x00\x00\x00");
+    raw.extend_from_slice(&[flags, 0, 0, 1, 0, 0, 1, id.len() as u8]);
+    raw.extend_from_slice(id);
+    if id.len().is_multiple_of(2) {
+        raw.push(0);
+    }
+    raw.extend_from_slice(system_use);
+    raw[0] = raw.len() as u8;
+    raw
+}
+
+/// Build a Rock Ridge entry holding a location (`CL` or `PL`).
+fn location(signature: &[u8], location: u32) -> Vec<u8> {
+    let mut raw = signature.to_vec();
+    raw.extend_from_slice(b"\x0C\x01");
+    raw.extend_from_slice(&location.to_le_bytes());
+    raw.extend_from_slice(&location.to_be_bytes());
+    raw
+}
+
+/// Write directory records at the start of the given sector.
+fn directory(image: &mut [u8], sector: usize, records: &[Vec<u8>]) {
+    let contents = records.concat();
+    image[sector * SECTOR..sector * SECTOR + contents.len()].copy_from_slice(&contents);
+}
+
+/// Build an image where `/A/DEEP` was relocated to `/RR_MOVED/DEEP`.
+///
+/// `parent` is the location recorded in the `PL` entry of the relocated
+/// directory, and `relocated` whether `RR_MOVED/DEEP` has an `RE` entry.
+fn image(parent: u32, relocated: bool) -> Vec<u8> {
+    let mut image = vec![0; 23 * SECTOR];
+
+    // Primary Volume Descriptor
+    let pvd = &mut image[16 * SECTOR..17 * SECTOR];
+    pvd[..8].copy_from_slice(b"\x01CD001\x01\x00");
+    pvd[8..72].iter_mut().for_each(|b| *b = b' ');
+    pvd[40..42].copy_from_slice(b"RR");
+    pvd[80..88].copy_from_slice(b"\x17\0\0\0\0\0\0\x17");
+    pvd[120..132].copy_from_slice(b"\x01\0\0\x01\x01\0\0\x01\0\x08\x08\0");
+    pvd[156..190].copy_from_slice(&record(b"\0", 18, 2048, 0x02, b""));
+    pvd[190..813].iter_mut().for_each(|b| *b = b' ');
+    for date in 0..4 {
+        let start = 813 + date * 17;
+        pvd[start..start + 16].iter_mut().for_each(|b| *b = b'0');
+    }
+    pvd[881] = 1;
+
+    // Volume Descriptor Set Terminator
+    image[17 * SECTOR..17 * SECTOR + 7].copy_from_slice(b"\xFFCD001\x01");
+
+    let root = [
+        record(b"\0", 18, 2048, 0x02, b"SP\x07\x01\xbe\xef\x00"),
+        record(b"\x01", 18, 2048, 0x02, b""),
+        record(b"A", 19, 2048, 0x02, b""),
+        record(b"RR_MOVED", 20, 2048, 0x02, b""),
+    ];
+    directory(&mut image, 18, &root);
+    let a = [
+        record(b"\0", 19, 2048, 0x02, b""),
+        record(b"\x01", 18, 2048, 0x02, b""),
+        record(b"DEEP", 0, 0, 0x00, &location(b"CL", 21)),
+    ];
+    directory(&mut image, 19, &a);
+    let moved = [
+        record(b"\0", 20, 2048, 0x02, b""),
+        record(b"\x01", 18, 2048, 0x02, b""),
+        record(b"DEEP", 21, 2048, 0x02, if relocated { b"RE\x04\x01" } else { b"" }),
+    ];
+    directory(&mut image, 20, &moved);
+    let deep = [
+        record(b"\0", 21, 2048, 0x02, b""),
+        record(b"\x01", 20, 2048, 0x02, &location(b"PL", parent)),
+        record(b"FILE.TXT;1", 22, 6, 0x00, b""),
+    ];
+    directory(&mut image, 21, &deep);
+    image[22 * SECTOR..22 * SECTOR + 6].copy_from_slice(b"hello\n");
+
+    image
+}
+
+#[test]
+fn test_relocation() {
+    let mut iso = IsoFs::from_buffer(image(19, true)).unwrap();
+
+    let names = iso
+        .read_dir("/")
+        .unwrap()
+        .iter()
+        .map(|entry| entry.name().to_owned())
+        .collect::<Vec<String>>();
+    assert_eq!(names, vec!["A"]);
+
+    assert!(iso.is_dir("/A/DEEP"));
+    let mut content = String::new();
+    iso.open_file("/A/DEEP/FILE.TXT")
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, "hello\n");
+    assert_eq!(iso.metadata("/A/DEEP/..").unwrap().path(), Path::new("/A"));
+}
+
+#[test]
+fn test_relocation_parent_mismatch() {
+    // a `PL` entry not pointing back to `/A` prevents the grafting
+    let mut iso = IsoFs::from_buffer(image(18, true)).unwrap();
+    assert!(iso.is_file("/A/DEEP"));
+    assert!(!iso.exists("/A/DEEP/FILE.TXT"));
+}
+
+#[test]
+fn test_relocation_directory_without_relocations() {
+    // `RR_MOVED` is a regular directory if it holds no `RE` entry
+    let mut iso = IsoFs::from_buffer(image(19, false)).unwrap();
+    assert!(iso.is_dir("/RR_MOVED"));
+    assert!(iso.is_dir("/RR_MOVED/DEEP"));
+}
@@ -1,3 +1,4 @@
+extern crate chrono;
 #[macro_use]
 extern crate lazy_static;
 extern crate opticaldisc;